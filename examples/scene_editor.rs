@@ -2,18 +2,33 @@
 
 use bevy::prelude::*;
 use bevy_editor_ai_test::editor;
+use std::path::PathBuf;
 
 fn main() {
-    App::new()
+    // `scene_editor [scene]` opens a scene file instead of the demo scene
+    let initial_scene = std::env::args_os().nth(1).map(PathBuf::from);
+
+    let mut app = App::new();
+    // Registers the asset source scenes are loaded from; must come before the AssetPlugin
+    app.add_plugins(editor::scene_asset::SceneSourcePlugin)
         // We use .set() here to configure the LogPlugin before it builds.
         // Bevy's LogPlugin initializes the global `tracing` subscriber immediately.
         // The `tracing` crate does not support replacing the global subscriber once initialized,
         // so we cannot "hook into" it later from the EditorPlugin.
-        .add_plugins(DefaultPlugins.set(editor::log::log_plugin()))
+        .add_plugins(DefaultPlugins.set(editor::log::log_plugin()).set(WindowPlugin {
+            // The editor asks about unsaved changes before closing
+            close_when_requested: false,
+            ..default()
+        }))
 
-        .add_plugins(editor::EditorPlugin)
-        .add_systems(Startup, setup_scene)
-        .run();
+        .add_plugins(editor::EditorPlugin {
+            initial_scene: initial_scene.clone(),
+            ..default()
+        });
+    if initial_scene.is_none() {
+        app.add_systems(Startup, setup_scene);
+    }
+    app.run();
 }
 
 
@@ -0,0 +1,12 @@
+#![deny(warnings)]
+
+//! Headless scene file tools: `bevy-editor-cli validate|convert|normalize|list ...`
+
+use bevy_editor_ai_test::editor::scene_tools;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut app = scene_tools::headless_app();
+    scene_tools::run(app.world_mut(), &args)
+}
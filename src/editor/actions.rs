@@ -3,11 +3,13 @@ use super::resources::{EditorState, InspectorUiState};
 use super::components::*;
 use bevy::ecs::system::Command;
 use crate::editor::menu::SceneInfo;
+use crate::editor::prefab::{PrefabLink, RevertOverrideCommand};
 
 type DeleteEntityFilter = (Changed<Interaction>, With<DeleteEntityButton>);
 type AddComponentToggleFilter = (Changed<Interaction>, With<AddComponentButton>);
 type RemoveComponentFilter = (Changed<Interaction>, With<RemoveComponentButton>);
 type ComponentAddConfirmFilter = (Changed<Interaction>, With<ComponentAddButton>);
+type RevertOverrideFilter = (Changed<Interaction>, With<RevertOverrideButton>);
 
 pub fn handle_delete_entity(
     interaction_query: Query<(&Interaction, &DeleteEntityButton), DeleteEntityFilter>,
@@ -114,9 +116,9 @@ pub fn handle_add_component_confirm(
 }
 
 
-struct AddComponentCommand {
-    entity: Entity,
-    type_id: std::any::TypeId,
+pub struct AddComponentCommand {
+    pub entity: Entity,
+    pub type_id: std::any::TypeId,
 }
 
 impl Command for AddComponentCommand {
@@ -137,6 +139,21 @@ impl Command for AddComponentCommand {
     }
 }
 
+pub fn handle_revert_override(
+    interaction_query: Query<(&Interaction, &RevertOverrideButton), RevertOverrideFilter>,
+    mut commands: Commands,
+) {
+    for (interaction, btn) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.queue(RevertOverrideCommand {
+                entity: btn.entity,
+                component: btn.component.clone(),
+                field: btn.field.clone(),
+            });
+        }
+    }
+}
+
 pub struct PropertyChangeCommand {
     pub entity: Entity,
     pub component_type_id: std::any::TypeId,
@@ -165,6 +182,10 @@ impl Command for PropertyChangeCommand {
                  applied = try_apply_value(field, &self.new_value);
              }
 
+             if applied && let Some(mut link) = world.get_mut::<PrefabLink>(self.entity) {
+                 link.add_override(registration.type_info().type_path(), &self.field_name);
+             }
+
              if applied && let Some(mut info) = world.get_resource_mut::<SceneInfo>() {
                  info.is_dirty = true;
              }
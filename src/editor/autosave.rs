@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, IoTaskPool, Task};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use super::dialog::{spawn_dialog, DialogChoice};
+use super::menu::{serialize_world_scene, write_atomic, LoadRequest, PendingAction, SceneInfo};
+
+#[derive(Resource, Clone, Debug)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// How long the scene stays dirty before it is written to its recovery file.
+    pub interval: Duration,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct AutosaveState {
+    /// Time the scene has been dirty since the last autosave.
+    pub dirty_for: Duration,
+    /// Recovery files written this session.
+    pub written: Vec<PathBuf>,
+    /// The recovery file write running on the IO task pool.
+    pub task: Option<(PathBuf, Task<std::io::Result<()>>)>,
+    /// The recovery file and scene last handed to `task`, so an unchanged scene isn't written again.
+    pub last_written: Option<(PathBuf, String)>,
+}
+
+impl AutosaveState {
+    /// Deletes the recovery files of `scene` and the ones written this session, after the scene was
+    /// saved or its changes were discarded.
+    pub fn remove_recovery_files(&mut self, scene: Option<&Path>) {
+        let recovery = recovery_path(scene);
+        for path in self.written.drain(..).chain(std::iter::once(recovery)) {
+            remove_recovery_file(&path);
+        }
+        self.last_written = None;
+    }
+}
+
+/// The recovery file of a scene: `level.scn.ron` is autosaved to `level.scn.ron.recovery` in the
+/// same directory. Untitled scenes use `untitled.scn.ron.recovery` in the working directory.
+pub fn recovery_path(scene: Option<&Path>) -> PathBuf {
+    match scene.and_then(|path| path.file_name().map(|name| (path, name))) {
+        Some((path, name)) => {
+            let mut name = name.to_os_string();
+            name.push(".recovery");
+            path.with_file_name(name)
+        }
+        None => std::env::current_dir().unwrap_or_default().join("untitled.scn.ron.recovery"),
+    }
+}
+
+/// Whether `recovery` exists and was written after the scene file was last saved.
+pub fn recovery_is_newer(scene: Option<&Path>, recovery: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let Some(recovery_time) = modified(recovery) else {
+        return false;
+    };
+    scene.and_then(modified).is_none_or(|scene_time| recovery_time > scene_time)
+}
+
+pub fn remove_recovery_file(path: &Path) {
+    match std::fs::remove_file(path) {
+        Ok(()) => info!("Removed recovery file {:?}", path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove recovery file {:?}: {}", path, e),
+    }
+}
+
+/// Writes the scene to its recovery file once it has been dirty for [`AutosaveSettings::interval`]
+/// and changed since the last autosave. The scene is serialized here and written on the IO task
+/// pool. The scene stays dirty; only a manual save clears it.
+pub fn autosave_system(world: &mut World) {
+    poll_autosave_task(world);
+    let settings = world.get_resource::<AutosaveSettings>().cloned().unwrap_or_default();
+    let Some(scene_info) = world.get_resource::<SceneInfo>() else {
+        return;
+    };
+    // The scene is about to be replaced or closed
+    if world.contains_resource::<PendingAction>() {
+        return;
+    }
+    let dirty = scene_info.is_dirty;
+    let path = recovery_path(scene_info.file_path.as_deref());
+    let delta = world.get_resource::<Time>().map_or(Duration::ZERO, |time| time.delta());
+
+    let mut state = world.get_resource_or_init::<AutosaveState>();
+    if !settings.enabled || !dirty {
+        state.dirty_for = Duration::ZERO;
+        return;
+    }
+    state.dirty_for += delta;
+    // Try again once the last write is done
+    if state.dirty_for < settings.interval || state.task.is_some() {
+        return;
+    }
+    state.dirty_for = Duration::ZERO;
+
+    let Some(serialized) = serialize_world_scene(world) else {
+        return;
+    };
+    let mut state = world.resource_mut::<AutosaveState>();
+    if state.last_written.as_ref().is_some_and(|(written, contents)| *written == path && *contents == serialized) {
+        return;
+    }
+    let contents = serialized.clone();
+    let target = path.clone();
+    let task = IoTaskPool::get().spawn(async move { write_atomic(&target, contents.as_bytes()) });
+    state.task = Some((path.clone(), task));
+    state.last_written = Some((path, serialized));
+}
+
+/// Collects the finished recovery file write. A write that finishes after the scene was saved or
+/// discarded would leave a stale recovery file behind, so that one is removed again.
+fn poll_autosave_task(world: &mut World) {
+    let dirty = world.get_resource::<SceneInfo>().is_some_and(|info| info.is_dirty);
+    let Some(mut state) = world.get_resource_mut::<AutosaveState>() else {
+        return;
+    };
+    let Some(result) = state.task.as_mut().and_then(|(_, task)| block_on(poll_once(task))) else {
+        return;
+    };
+    let Some((path, _)) = state.task.take() else {
+        return;
+    };
+    match result {
+        Ok(()) if dirty => {
+            debug!("Autosaved scene to {:?}", path);
+            if !state.written.contains(&path) {
+                state.written.push(path);
+            }
+        }
+        Ok(()) => remove_recovery_file(&path),
+        Err(e) => {
+            error!("Failed to autosave scene to {:?}: {}", path, e);
+            state.last_written = None;
+        }
+    }
+}
+
+/// Offers to restore the scene's recovery file on startup when it is newer than the scene on disk.
+/// A scene loaded on startup makes the offer once it is loaded instead.
+pub fn offer_recovery_system(mut commands: Commands, scene_info: Res<SceneInfo>, load_request: Option<Res<LoadRequest>>) {
+    if load_request.is_none() {
+        offer_recovery(&mut commands, scene_info.file_path.as_deref());
+    }
+}
+
+/// Offers to restore the recovery file of `scene` if it is newer than the scene on disk.
+pub fn offer_recovery(commands: &mut Commands, scene: Option<&Path>) {
+    let recovery = recovery_path(scene);
+    if !recovery_is_newer(scene, &recovery) {
+        return;
+    }
+
+    let name = scene
+        .and_then(|path| path.file_name())
+        .map_or("the untitled scene".to_string(), |name| name.to_string_lossy().into_owned());
+    info!("Found recovery file {:?}", recovery);
+    spawn_dialog(
+        commands,
+        &format!("Unsaved changes to {} were recovered. Restore them?", name),
+        vec![
+            ("Restore".to_string(), DialogChoice::RestoreRecovery(recovery.clone())),
+            ("Discard".to_string(), DialogChoice::DiscardRecovery(recovery)),
+        ],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_file_sits_next_to_the_scene() {
+        let scene = Path::new("levels").join("level.scn.ron");
+        assert_eq!(recovery_path(Some(&scene)), Path::new("levels").join("level.scn.ron.recovery"));
+        assert_eq!(recovery_path(None).file_name().unwrap(), "untitled.scn.ron.recovery");
+        assert!(!recovery_is_newer(Some(&scene), &recovery_path(Some(&scene))), "A missing recovery file is never newer");
+    }
+}
@@ -1,12 +1,30 @@
 use bevy::prelude::*;
+use bevy::camera::primitives::{Aabb, MeshAabb};
+use bevy::ecs::system::Command;
 use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll};
-use crate::editor::resources::IsResizing;
+use crate::editor::input::EditorKeys;
+use crate::editor::keymap::{EditorAction, EditorKeymap};
+use crate::editor::resources::{EditorState, IsResizing};
 use bevy::ecs::system::SystemParam;
+use crate::editor::save_filter::EditorOnly;
 
 #[derive(Component)]
+#[require(EditorOnly)]
 pub struct EditorCamera {
     pub speed: f32,
     pub sensitivity: f32,
+    /// Point the camera orbits around and zooms toward. Panning and flying carry it along.
+    pub pivot: Vec3,
+    /// Set while the camera moves to frame an entity.
+    pub framing: Option<CameraFraming>,
+}
+
+/// A camera move started by [`FrameEntity`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraFraming {
+    pub start: Vec3,
+    pub target: Vec3,
+    pub elapsed: f32,
 }
 
 impl Default for EditorCamera {
@@ -14,22 +32,57 @@ impl Default for EditorCamera {
         Self {
             speed: 10.0,
             sensitivity: 0.003,
+            pivot: Vec3::ZERO,
+            framing: None,
         }
     }
 }
 
+/// Keeps pitch just short of straight up or down to avoid flipping over.
+const MAX_PITCH: f32 = 1.54;
+/// Zooming never gets closer to the pivot than this.
+const MIN_PIVOT_DISTANCE: f32 = 0.1;
+/// Fraction of the distance to the pivot left after one scroll step in.
+const ZOOM_FACTOR: f32 = 0.85;
+
+/// Turns the camera around `pivot` by a mouse movement, keeping its distance and looking at it.
+pub fn orbit_around(transform: &mut Transform, pivot: Vec3, delta: Vec2, sensitivity: f32) {
+    let distance = transform.translation.distance(pivot);
+    let (mut yaw, mut pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    yaw -= delta.x * sensitivity;
+    pitch = (pitch - delta.y * sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    transform.translation = pivot + *transform.back() * distance;
+}
+
+/// Moves the camera toward `pivot` by `scroll` steps (away when negative), never onto it.
+pub fn zoom_towards(transform: &mut Transform, pivot: Vec3, scroll: f32) {
+    let offset = transform.translation - pivot;
+    let distance = (offset.length() * ZOOM_FACTOR.powf(scroll)).max(MIN_PIVOT_DISTANCE);
+    let direction = offset.try_normalize().unwrap_or(*transform.back());
+    transform.translation = pivot + direction * distance;
+}
+
 #[derive(SystemParam)]
 pub struct EditorInput<'w> {
-    keys: Res<'w, ButtonInput<KeyCode>>,
+    /// Keys not taken by a focused text field.
+    keys: Res<'w, EditorKeys>,
+    keymap: Res<'w, EditorKeymap>,
     mouse: Res<'w, ButtonInput<MouseButton>>,
     mouse_motion: Res<'w, AccumulatedMouseMotion>,
     mouse_scroll: Res<'w, AccumulatedMouseScroll>,
     is_resizing: Res<'w, IsResizing>,
 }
 
+impl EditorInput<'_> {
+    fn pressed(&self, action: EditorAction) -> bool {
+        self.keymap.pressed(action, &self.keys.0)
+    }
+}
+
 pub fn editor_camera_controls(
     mut windows: Query<&mut Window>,
-    mut query: Query<(&EditorCamera, &mut Transform)>,
+    mut query: Query<(&mut EditorCamera, &mut Transform)>,
     input: EditorInput,
     time: Res<Time>,
 ) {
@@ -43,6 +96,8 @@ pub fn editor_camera_controls(
 
     let rmb_held = input.mouse.pressed(MouseButton::Right);
     let lmb_held = input.mouse.pressed(MouseButton::Left);
+    let orbiting = input.mouse.pressed(MouseButton::Middle)
+        || (lmb_held && input.pressed(EditorAction::CameraOrbit));
 
     // Cursor handling (commented out due to API issues)
     // if rmb_held {
@@ -53,9 +108,18 @@ pub fn editor_camera_controls(
     //    window.cursor_options.grab_mode = bevy::window::CursorGrabMode::None;
     // }
 
-    for (camera, mut transform) in query.iter_mut() {
+    for (mut camera, mut transform) in query.iter_mut() {
+        // Taking over the camera stops framing. A plain left click doesn't, as it may be the
+        // double click on the hierarchy that started it.
+        if rmb_held || orbiting || input.mouse_scroll.delta.y != 0.0 {
+            camera.framing = None;
+        }
+
         // 1. Fly Controls (RMB Held)
         if rmb_held {
+            // The pivot stays the same distance in front of the camera
+            let pivot_distance = transform.translation.distance(camera.pivot);
+
             // Rotation
             let rotation_move = input.mouse_motion.delta;
 
@@ -65,7 +129,7 @@ pub fn editor_camera_controls(
                 pitch -= rotation_move.y * camera.sensitivity;
 
                 // Clamp pitch to avoid flipping over
-                pitch = pitch.clamp(-1.54, 1.54);
+                pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
 
                 transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
             }
@@ -76,22 +140,22 @@ pub fn editor_camera_controls(
             let local_right = transform.right();
             let local_up = transform.up();
 
-            if input.keys.pressed(KeyCode::KeyW) {
+            if input.pressed(EditorAction::CameraForward) {
                 velocity += *local_forward;
             }
-            if input.keys.pressed(KeyCode::KeyS) {
+            if input.pressed(EditorAction::CameraBack) {
                 velocity -= *local_forward;
             }
-            if input.keys.pressed(KeyCode::KeyA) {
+            if input.pressed(EditorAction::CameraLeft) {
                 velocity -= *local_right;
             }
-            if input.keys.pressed(KeyCode::KeyD) {
+            if input.pressed(EditorAction::CameraRight) {
                 velocity += *local_right;
             }
-            if input.keys.pressed(KeyCode::KeyE) {
+            if input.pressed(EditorAction::CameraUp) {
                 velocity += *local_up;
             }
-            if input.keys.pressed(KeyCode::KeyQ) {
+            if input.pressed(EditorAction::CameraDown) {
                 velocity -= *local_up;
             }
 
@@ -102,15 +166,24 @@ pub fn editor_camera_controls(
 
             // Apply speed
             let mut current_speed = camera.speed;
-            if input.keys.pressed(KeyCode::ShiftLeft) || input.keys.pressed(KeyCode::ShiftRight) {
+            if input.pressed(EditorAction::CameraBoost) {
                 current_speed *= 2.0;
             }
 
             let translation = velocity * current_speed * time.delta_secs();
             transform.translation += translation;
+            camera.pivot = transform.translation + *transform.forward() * pivot_distance;
+
+        } else if orbiting {
+            // 2. Orbit Controls (Alt+LMB or MMB Held)
+            let orbit_move = input.mouse_motion.delta;
+
+            if orbit_move.length_squared() > 0.0 {
+                orbit_around(&mut transform, camera.pivot, orbit_move, camera.sensitivity);
+            }
 
         } else if lmb_held {
-            // 2. Pan Controls (LMB Held)
+            // 3. Pan Controls (LMB Held)
             // "Pulling the world" style:
             // Drag Left (negative X) -> Camera moves Right (positive local X)
             // Drag Up (positive Y) -> Camera moves Down (negative local Y)
@@ -122,19 +195,133 @@ pub fn editor_camera_controls(
                 let local_right = transform.right();
                 let local_up = transform.up();
 
-                transform.translation -= *local_right * pan_move.x * pan_sensitivity;
-                transform.translation += *local_up * pan_move.y * pan_sensitivity;
+                let offset = (*local_up * pan_move.y - *local_right * pan_move.x) * pan_sensitivity;
+                transform.translation += offset;
+                camera.pivot += offset;
             }
         }
 
-        // 3. Zoom (Always Active)
-        // Simple zoom: move forward/back
+        // 4. Zoom (Always Active)
+        // Move toward the pivot, slowing down as it gets close
         let scroll = input.mouse_scroll.delta.y;
         if scroll != 0.0 {
-            // Zoom speed multiplier
-            let zoom_speed = 2.0;
-            let forward = transform.forward();
-            transform.translation += *forward * scroll * zoom_speed;
+            zoom_towards(&mut transform, camera.pivot, scroll);
+        }
+    }
+}
+
+/// Seconds a [`FrameEntity`] move takes.
+const FRAME_DURATION: f32 = 0.3;
+/// Bounding radius of entities without a mesh, e.g. lights and empties.
+pub const DEFAULT_FRAME_RADIUS: f32 = 1.0;
+/// Space left around a framed entity, as a multiple of its radius.
+const FRAME_MARGIN: f32 = 1.2;
+
+/// World-space bounding sphere of `entity` and its descendants, from their `Aabb`s or, before
+/// those are computed, their meshes. Entities without either get [`DEFAULT_FRAME_RADIUS`].
+pub fn world_bounds(world: &mut World, entity: Entity) -> Option<(Vec3, f32)> {
+    let origin = world.get::<GlobalTransform>(entity)?.translation();
+    let mut entities = vec![entity];
+    entities.extend(world.query::<&Children>().query(world).iter_descendants(entity));
+
+    let mut min = Vec3::MAX;
+    let mut max = Vec3::MIN;
+    for entity in entities {
+        let Some(transform) = world.get::<GlobalTransform>(entity) else {
+            continue;
+        };
+        let aabb = world.get::<Aabb>(entity).copied().or_else(|| {
+            let mesh = world.get::<Mesh3d>(entity)?;
+            world.get_resource::<Assets<Mesh>>()?.get(&mesh.0)?.compute_aabb()
+        });
+        let Some(aabb) = aabb else {
+            continue;
+        };
+        let (center, half) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+        for corner in 0..8 {
+            let sign = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let point = transform.transform_point(center + half * sign);
+            min = min.min(point);
+            max = max.max(point);
+        }
+    }
+
+    if min.cmpgt(max).any() {
+        return Some((origin, DEFAULT_FRAME_RADIUS));
+    }
+    Some(((min + max) / 2.0, ((max - min).length() / 2.0).max(MIN_PIVOT_DISTANCE)))
+}
+
+/// How far from the center of a sphere of `radius` a camera with vertical field of view `fov_y`
+/// has to be to fit it in a viewport of the given aspect ratio.
+pub fn framing_distance(radius: f32, fov_y: f32, aspect: f32) -> f32 {
+    let half_fov_y = fov_y / 2.0;
+    let half_fov = half_fov_y.min((half_fov_y.tan() * aspect).atan());
+    radius * FRAME_MARGIN / half_fov.sin()
+}
+
+/// Moves the editor camera, keeping its rotation, until the entity fills the viewport, and makes
+/// the entity the orbit pivot.
+pub struct FrameEntity(pub Entity);
+
+impl Command for FrameEntity {
+    fn apply(self, world: &mut World) {
+        let Some((center, radius)) = world_bounds(world, self.0) else {
+            return;
+        };
+        let mut cameras = world.query::<(&mut EditorCamera, &Transform, &Camera, &Projection)>();
+        for (mut editor_camera, transform, camera, projection) in cameras.iter_mut(world) {
+            // The viewport size comes from `sync_camera_viewport`
+            let (fov, aspect) = match projection {
+                Projection::Perspective(perspective) => (
+                    perspective.fov,
+                    camera.logical_viewport_size().map_or(perspective.aspect_ratio, |size| size.x / size.y),
+                ),
+                _ => (PerspectiveProjection::default().fov, 1.0),
+            };
+            let distance = framing_distance(radius, fov, aspect);
+            editor_camera.pivot = center;
+            editor_camera.framing = Some(CameraFraming {
+                start: transform.translation,
+                target: center + *transform.back() * distance,
+                elapsed: 0.0,
+            });
+        }
+    }
+}
+
+/// Frames the selected entity, if any.
+pub struct FrameSelection;
+
+impl Command for FrameSelection {
+    fn apply(self, world: &mut World) {
+        if let Some(entity) = world.get_resource::<EditorState>().and_then(|state| state.selected_entity) {
+            FrameEntity(entity).apply(world);
+        }
+    }
+}
+
+pub fn frame_selection_shortcut(mut commands: Commands, keys: Res<EditorKeys>, keymap: Res<EditorKeymap>) {
+    if keymap.just_pressed(EditorAction::FrameSelection, &keys.0) {
+        commands.queue(FrameSelection);
+    }
+}
+
+/// Eases cameras toward the target of their [`CameraFraming`].
+pub fn animate_camera_framing(time: Res<Time>, mut cameras: Query<(&mut EditorCamera, &mut Transform)>) {
+    for (mut camera, mut transform) in cameras.iter_mut() {
+        let Some(framing) = camera.framing.as_mut() else {
+            continue;
+        };
+        framing.elapsed += time.delta_secs();
+        let t = (framing.elapsed / FRAME_DURATION).min(1.0);
+        transform.translation = framing.start.lerp(framing.target, t * t * (3.0 - 2.0 * t));
+        if t >= 1.0 {
+            camera.framing = None;
         }
     }
 }
@@ -242,7 +429,7 @@ pub fn sync_camera_viewport(
 
         // const REFERENCE_FOV_Y: f32 = std::f32::consts::FRAC_PI_3; // 60 degrees - Unused
         const REFERENCE_HEIGHT: f32 = 600.0;
-        const PIXEL_SCALE_FACTOR: f32 = (0.577350269) / REFERENCE_HEIGHT; // tan(30deg) / 600
+        const PIXEL_SCALE_FACTOR: f32 = 0.577_350_26 / REFERENCE_HEIGHT; // tan(30deg) / 600
 
 
         if let Projection::Perspective(ref mut pers) = *projection {
@@ -267,6 +454,7 @@ pub fn setup_editor_cameras(mut commands: Commands) {
             order: 1,
             ..default()
         },
+        EditorOnly,
     ));
 
     // 2. Editor Scene Camera (Renders the 3D World)
@@ -311,6 +499,7 @@ mod tests {
         // Spawn Camera
         let camera_entity = app.world_mut().spawn((
             Camera::default(),
+            Projection::default(),
             EditorCamera::default(),
         )).id();
 
@@ -352,4 +541,123 @@ mod tests {
         assert_eq!(viewport.physical_size.x, 400);
         assert_eq!(viewport.physical_size.y, 300);
     }
+
+    #[test]
+    fn test_orbit_and_zoom_around_pivot() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<EditorKeys>()
+            .init_resource::<EditorKeymap>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<AccumulatedMouseMotion>()
+            .init_resource::<AccumulatedMouseScroll>()
+            .init_resource::<IsResizing>();
+        app.world_mut().spawn(Window::default());
+        app.add_systems(Update, editor_camera_controls);
+
+        let pivot = Vec3::new(1.0, 0.0, -2.0);
+        let camera = app.world_mut().spawn((
+            EditorCamera { pivot, ..default() },
+            Transform::from_xyz(1.0, 0.0, 3.0).looking_at(pivot, Vec3::Y),
+        )).id();
+
+        // Alt+LMB orbits: same distance, still looking at the pivot
+        app.world_mut().resource_mut::<EditorKeys>().0.press(KeyCode::AltLeft);
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+        app.world_mut().resource_mut::<AccumulatedMouseMotion>().delta = Vec2::new(200.0, 50.0);
+        app.update();
+
+        let transform = *app.world().get::<Transform>(camera).unwrap();
+        assert!((transform.translation.distance(pivot) - 5.0).abs() < 1e-4);
+        assert!(transform.translation.x < 1.0, "orbiting moved the camera sideways");
+        assert!(transform.forward().dot((pivot - transform.translation).normalize()) > 0.9999);
+        assert_eq!(app.world().get::<EditorCamera>(camera).unwrap().pivot, pivot);
+
+        // Zooming closes in on the pivot without reaching it
+        app.world_mut().resource_mut::<EditorKeys>().0.release(KeyCode::AltLeft);
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().release(MouseButton::Left);
+        app.world_mut().resource_mut::<AccumulatedMouseMotion>().delta = Vec2::ZERO;
+        app.world_mut().resource_mut::<AccumulatedMouseScroll>().delta = Vec2::new(0.0, 1000.0);
+        app.update();
+
+        let zoomed = *app.world().get::<Transform>(camera).unwrap();
+        assert!((zoomed.translation.distance(pivot) - MIN_PIVOT_DISTANCE).abs() < 1e-4);
+        assert!(zoomed.forward().dot((pivot - zoomed.translation).normalize()) > 0.9999);
+
+        // Panning carries the pivot along
+        app.world_mut().resource_mut::<AccumulatedMouseScroll>().delta = Vec2::ZERO;
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+        app.world_mut().resource_mut::<AccumulatedMouseMotion>().delta = Vec2::new(-100.0, 0.0);
+        app.update();
+
+        let panned = *app.world().get::<Transform>(camera).unwrap();
+        let moved_pivot = app.world().get::<EditorCamera>(camera).unwrap().pivot;
+        assert_ne!(moved_pivot, pivot);
+        assert!((moved_pivot - panned.translation - (pivot - zoomed.translation)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_frame_selection_with_f_and_double_click() {
+        use crate::editor::components::HierarchyEntityRow;
+        use crate::editor::hierarchy::update_hierarchy;
+        use bevy::camera::primitives::Aabb;
+        use bevy::time::TimeUpdateStrategy;
+        use std::time::Duration;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.init_resource::<EditorKeys>()
+            .init_resource::<EditorKeymap>()
+            .init_resource::<EditorState>();
+        app.add_systems(Update, (frame_selection_shortcut, update_hierarchy, animate_camera_framing).chain());
+
+        let camera = app.world_mut().spawn((
+            Camera::default(),
+            Projection::default(),
+            Transform::from_xyz(0.0, 0.0, 5.0),
+            EditorCamera::default(),
+        )).id();
+        let cube = app.world_mut().spawn((
+            Transform::from_xyz(10.0, 0.0, 0.0),
+            GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0)),
+            Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+        )).id();
+        let light = app.world_mut().spawn(GlobalTransform::from_translation(Vec3::new(0.0, 4.0, 0.0))).id();
+
+        let (center, radius) = world_bounds(app.world_mut(), cube).unwrap();
+        assert!(center.distance(Vec3::new(10.0, 0.0, 0.0)) < 1e-4);
+        assert!((radius - 3.0_f32.sqrt()).abs() < 1e-4);
+        assert_eq!(world_bounds(app.world_mut(), light), Some((Vec3::new(0.0, 4.0, 0.0), DEFAULT_FRAME_RADIUS)));
+
+        // F frames the selection, easing the camera over several frames
+        app.world_mut().resource_mut::<EditorState>().selected_entity = Some(cube);
+        app.world_mut().resource_mut::<EditorKeys>().0.press(KeyCode::KeyF);
+        app.update();
+        app.world_mut().resource_mut::<EditorKeys>().0.clear();
+        app.world_mut().resource_mut::<EditorKeys>().0.release(KeyCode::KeyF);
+
+        let distance = framing_distance(3.0_f32.sqrt(), PerspectiveProjection::default().fov, 1.0);
+        let target = Vec3::new(10.0, 0.0, distance);
+        assert_eq!(app.world().get::<EditorCamera>(camera).unwrap().pivot, center);
+        app.update();
+        app.update();
+        let midway = app.world().get::<Transform>(camera).unwrap().translation;
+        assert!(midway.x > 0.0 && midway.x < 10.0, "the camera jumped to {midway}");
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world().get::<Transform>(camera).unwrap().translation.distance(target) < 1e-4);
+        assert!(app.world().get::<EditorCamera>(camera).unwrap().framing.is_none());
+
+        // Double clicking a hierarchy row frames its entity
+        let row = app.world_mut().spawn((Interaction::None, HierarchyEntityRow { entity: light })).id();
+        for interaction in [Interaction::Pressed, Interaction::None, Interaction::Pressed] {
+            *app.world_mut().get_mut::<Interaction>(row).unwrap() = interaction;
+            app.update();
+        }
+        let editor_camera = app.world().get::<EditorCamera>(camera).unwrap();
+        assert_eq!(editor_camera.pivot, Vec3::new(0.0, 4.0, 0.0));
+        assert!(editor_camera.framing.is_some());
+    }
 }
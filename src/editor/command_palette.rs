@@ -0,0 +1,362 @@
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use std::sync::Arc;
+use super::actions::AddComponentCommand;
+use super::camera::{FrameEntity, FrameSelection};
+use super::components::{HierarchyPanel, InspectorPanel, MenuAction};
+use super::input::{EditorKeys, TypedText};
+use super::keymap::{EditorAction, EditorKeymap, ShortcutsPanel};
+use super::log::LogPanel;
+use super::menu::{RunMenuAction, SceneInfo};
+use super::primitives::{PrimitiveMesh, PrimitiveShape};
+use super::resources::EditorState;
+use super::save_filter::SceneEntityFilter;
+use super::styles::*;
+use super::text_field::{text_field_text, TextField};
+use super::ui::ToggleEditorCommand;
+
+/// How many matches the palette shows.
+const MAX_RESULTS: usize = 12;
+
+/// A named action listed in the command palette.
+#[derive(Clone)]
+pub struct EditorCommand {
+    pub name: String,
+    pub run: Arc<dyn Fn(&mut World) + Send + Sync>,
+}
+
+impl EditorCommand {
+    pub fn new(name: impl Into<String>, run: impl Fn(&mut World) + Send + Sync + 'static) -> Self {
+        Self { name: name.into(), run: Arc::new(run) }
+    }
+}
+
+/// Commands offered by the command palette, starting with the editor's own. Plugins add theirs
+/// with [`EditorCommands::register`]. Components to add and entities to focus are listed on top of
+/// these when the palette opens.
+#[derive(Resource, Clone)]
+pub struct EditorCommands(pub Vec<EditorCommand>);
+
+impl EditorCommands {
+    pub fn register(&mut self, name: impl Into<String>, run: impl Fn(&mut World) + Send + Sync + 'static) -> &mut Self {
+        self.0.push(EditorCommand::new(name, run));
+        self
+    }
+}
+
+impl Default for EditorCommands {
+    fn default() -> Self {
+        let mut commands = Self(Vec::new());
+        for (name, action) in [
+            ("File: New Scene...", MenuAction::NewScene),
+            ("File: Save", MenuAction::Save),
+            ("File: Save As...", MenuAction::SaveAs),
+            ("File: Load...", MenuAction::Load),
+            ("File: Import...", MenuAction::Import),
+            ("File: Import Under Parent...", MenuAction::ImportUnderParent),
+            ("File: Save as Prefab...", MenuAction::SavePrefab),
+            ("File: Instantiate Prefab...", MenuAction::InstantiatePrefab),
+            ("File: Exit", MenuAction::Exit),
+        ] {
+            commands.register(name, move |world| RunMenuAction(action).apply(world));
+        }
+        commands
+            .register("View: Toggle Editor", |world| ToggleEditorCommand.apply(world))
+            .register("View: Frame Selection", |world| FrameSelection.apply(world))
+            .register("View: Toggle Hierarchy", toggle_panel::<HierarchyPanel>)
+            .register("View: Toggle Inspector", toggle_panel::<InspectorPanel>)
+            .register("View: Toggle Output", toggle_panel::<LogPanel>)
+            .register("View: Keyboard Shortcuts", |world| {
+                let mut panels = world.query_filtered::<&mut Visibility, With<ShortcutsPanel>>();
+                for mut vis in panels.iter_mut(world) {
+                    *vis = match *vis {
+                        Visibility::Hidden => Visibility::Inherited,
+                        _ => Visibility::Hidden,
+                    };
+                }
+            });
+        for primitive in Primitive::ALL {
+            commands.register(format!("Spawn: {}", primitive.name()), move |world| SpawnPrimitive(primitive).apply(world));
+        }
+        commands
+    }
+}
+
+/// Shows or hides the editor panel marked with `T`.
+fn toggle_panel<T: Component>(world: &mut World) {
+    let mut panels = world.query_filtered::<&mut Node, With<T>>();
+    for mut node in panels.iter_mut(world) {
+        node.display = match node.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Empty,
+    Cube,
+    Sphere,
+    Plane,
+    PointLight,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 5] = [Primitive::Empty, Primitive::Cube, Primitive::Sphere, Primitive::Plane, Primitive::PointLight];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Empty => "Empty Entity",
+            Primitive::Cube => "Cube",
+            Primitive::Sphere => "Sphere",
+            Primitive::Plane => "Plane",
+            Primitive::PointLight => "Point Light",
+        }
+    }
+
+    fn shape(self) -> Option<PrimitiveShape> {
+        match self {
+            Primitive::Cube => Some(PrimitiveShape::Cube),
+            Primitive::Sphere => Some(PrimitiveShape::Sphere),
+            Primitive::Plane => Some(PrimitiveShape::Plane { size: Vec2::splat(5.0) }),
+            Primitive::Empty | Primitive::PointLight => None,
+        }
+    }
+}
+
+/// Spawns a primitive at the origin and selects it.
+pub struct SpawnPrimitive(pub Primitive);
+
+impl Command for SpawnPrimitive {
+    fn apply(self, world: &mut World) {
+        let mut entity = world.spawn((Name::new(self.0.name()), Transform::default(), Visibility::default()));
+        if let Some(shape) = self.0.shape() {
+            // The mesh itself is built by `build_primitive_meshes`
+            entity.insert(PrimitiveMesh { shape, ..default() });
+        }
+        if self.0 == Primitive::PointLight {
+            entity.insert(PointLight { shadows_enabled: true, ..default() });
+        }
+        let entity = entity.id();
+
+        if let Some(mut state) = world.get_resource_mut::<EditorState>() {
+            state.selected_entity = Some(entity);
+        }
+        if let Some(mut info) = world.get_resource_mut::<SceneInfo>() {
+            info.is_dirty = true;
+        }
+    }
+}
+
+/// Scores how well `candidate` matches a typed filter, lower being better. Every character of the
+/// filter has to appear in order, ignoring case; consecutive matches near the start score best.
+pub fn fuzzy_score(candidate: &str, filter: &str) -> Option<usize> {
+    let candidate = candidate.to_lowercase();
+    let filter = filter.to_lowercase();
+    if let Some(position) = candidate.find(&filter) {
+        return Some(position);
+    }
+    let mut chars = candidate.char_indices();
+    let mut score = candidate.len();
+    let mut last = 0;
+    for wanted in filter.chars() {
+        let (index, _) = chars.find(|&(_, c)| c == wanted)?;
+        score += index - last;
+        last = index;
+    }
+    Some(score)
+}
+
+/// The items whose name matches `filter`, best first. Equally good matches keep their order.
+pub fn fuzzy_filter<'n, T>(items: impl IntoIterator<Item = T>, name: impl Fn(&T) -> &'n str, filter: &str) -> Vec<T> {
+    let mut scored: Vec<(usize, T)> = items
+        .into_iter()
+        .filter_map(|item| fuzzy_score(name(&item), filter).map(|score| (score, item)))
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Everything the palette can run right now: the registered commands, components to add to the
+/// selected entity and named scene entities to select.
+pub fn palette_commands(world: &mut World) -> Vec<EditorCommand> {
+    let mut commands = world.get_resource::<EditorCommands>().map(|c| c.0.clone()).unwrap_or_default();
+
+    if let Some(entity) = world.resource::<EditorState>().selected_entity {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        for registration in registry.iter() {
+            if registration.data::<ReflectComponent>().is_some() && registration.data::<ReflectDefault>().is_some() {
+                let name = registration.type_info().type_path_table().short_path();
+                let type_id = registration.type_id();
+                commands.push(EditorCommand::new(format!("Add Component: {}", name), move |world| {
+                    AddComponentCommand { entity, type_id }.apply(world);
+                }));
+            }
+        }
+    }
+
+    let mut named = world.query_filtered::<(Entity, &Name), SceneEntityFilter>();
+    for (entity, name) in named.iter(world) {
+        commands.push(EditorCommand::new(format!("Focus: {}", name), move |world| {
+            if let Some(mut state) = world.get_resource_mut::<EditorState>() {
+                state.selected_entity = Some(entity);
+            }
+            FrameEntity(entity).apply(world);
+        }));
+    }
+    commands
+}
+
+/// The open palette and what it offers.
+#[derive(Component)]
+pub struct CommandPalette {
+    pub commands: Vec<EditorCommand>,
+}
+
+#[derive(Component)]
+pub struct CommandPaletteInput;
+
+#[derive(Component)]
+pub struct CommandPaletteResults;
+
+/// A listed match, indexing [`CommandPalette::commands`].
+#[derive(Component)]
+pub struct CommandPaletteEntry(pub usize);
+
+/// Opens the command palette with its search field focused.
+pub struct OpenCommandPalette;
+
+impl Command for OpenCommandPalette {
+    fn apply(self, world: &mut World) {
+        let mut open = world.query_filtered::<(), With<CommandPalette>>();
+        if open.iter(world).next().is_some() {
+            return;
+        }
+        let commands = palette_commands(world);
+
+        let mut input = Entity::PLACEHOLDER;
+        world
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.0),
+                    left: Val::Percent(30.0),
+                    width: Val::Percent(40.0),
+                    flex_direction: FlexDirection::Column,
+                    border: UiRect::all(Val::Px(1.0)),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(PANEL_COLOR),
+                GlobalZIndex(30),
+                CommandPalette { commands },
+            ))
+            .with_children(|palette| {
+                input = palette
+                    .spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            border: UiRect::all(Val::Px(1.0)),
+                            ..default()
+                        },
+                        BackgroundColor(BACKGROUND_COLOR),
+                        CommandPaletteInput,
+                        TextField::new(""),
+                        children![text_field_text(14.0)],
+                    ))
+                    .id();
+                palette.spawn((
+                    Node { flex_direction: FlexDirection::Column, ..default() },
+                    CommandPaletteResults,
+                ));
+            });
+        world.resource_mut::<EditorState>().focused_input = Some(input);
+    }
+}
+
+pub fn command_palette_shortcut(mut commands: Commands, keys: Res<EditorKeys>, keymap: Res<EditorKeymap>) {
+    if keymap.just_pressed(EditorAction::CommandPalette, &keys.0) {
+        commands.queue(OpenCommandPalette);
+    }
+}
+
+/// Lists the commands matching the palette's search, runs the picked one (clicked, or the best
+/// match on Enter) and closes the palette once its search field loses focus.
+pub fn command_palette_system(
+    mut commands: Commands,
+    typed: Res<TypedText>,
+    mut editor_state: ResMut<EditorState>,
+    palettes: Query<(Entity, &CommandPalette)>,
+    inputs: Query<(Entity, Ref<TextField>), With<CommandPaletteInput>>,
+    results: Query<Entity, With<CommandPaletteResults>>,
+    entries: Query<(&Interaction, &CommandPaletteEntry), Changed<Interaction>>,
+) {
+    let Ok((palette_entity, palette)) = palettes.single() else {
+        return;
+    };
+    let Ok((input, field)) = inputs.single() else {
+        return;
+    };
+    let matches = || fuzzy_filter(0..palette.commands.len(), |&i| &palette.commands[i].name, &field.text);
+
+    let focused = editor_state.focused_input == Some(input);
+    let picked = entries
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, entry)| entry.0)
+        .or_else(|| (focused && typed.submitted()).then(|| matches().first().copied()).flatten());
+    if let Some(index) = picked {
+        let run = palette.commands[index].run.clone();
+        commands.queue(move |world: &mut World| run(world));
+    }
+    if picked.is_some() || !focused {
+        commands.entity(palette_entity).despawn();
+        if focused {
+            editor_state.focused_input = None;
+        }
+        return;
+    }
+
+    if !field.is_changed() {
+        return;
+    }
+    for results in results.iter() {
+        commands.entity(results).despawn_related::<Children>().with_children(|list| {
+            for index in matches().into_iter().take(MAX_RESULTS) {
+                list.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::all(Val::Px(3.0)),
+                        width: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    CommandPaletteEntry(index),
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new(palette.commands[index].name.clone()),
+                        TextFont { font_size: 12.0, ..default() },
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_filter_ranks_matches() {
+        assert_eq!(
+            fuzzy_filter(["Spawn: Sphere", "File: Save", "File: Save As..."], |name| name, "fsave"),
+            vec!["File: Save", "File: Save As..."]
+        );
+        assert_eq!(fuzzy_filter(["Spawn: Sphere", "Add Component: Sprite"], |name| name, "sprite"), vec!["Add Component: Sprite"]);
+        assert!(fuzzy_filter(["File: Save"], |name| name, "load").is_empty());
+    }
+}
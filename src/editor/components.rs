@@ -1,6 +1,8 @@
 use bevy::prelude::*;
+use super::save_filter::EditorOnly;
 
 #[derive(Component)]
+#[require(EditorOnly)]
 pub struct EditorRoot;
 
 #[derive(Component)]
@@ -12,11 +14,22 @@ pub struct FileMenuButton;
 #[derive(Component)]
 pub struct FileMenuDropdown;
 
+/// Menu bar text showing the outcome of the last save.
 #[derive(Component)]
+pub struct SaveStatusText;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MenuAction {
+    NewScene,
     Save,
     SaveAs,
     Load,
+    RecentScenes,
+    Import,
+    /// Import under a new entity named after the file, whatever [`EditorConfig::import_under_parent`](super::resources::EditorConfig::import_under_parent) says.
+    ImportUnderParent,
+    SavePrefab,
+    InstantiatePrefab,
     Exit,
 }
 
@@ -25,6 +38,16 @@ pub struct MenuButtonAction {
     pub action: MenuAction,
 }
 
+/// Submenu listing [`EditorPreferences::recent_scenes`](super::preferences::EditorPreferences::recent_scenes).
+#[derive(Component)]
+pub struct RecentScenesMenu;
+
+#[derive(Component)]
+pub struct RecentSceneButton(pub std::path::PathBuf);
+
+#[derive(Component)]
+pub struct ClearRecentButton;
+
 #[derive(Component)]
 pub struct HierarchyPanel;
 
@@ -51,18 +74,32 @@ pub struct AddComponentButton;
 #[derive(Component)]
 pub struct RemoveComponentButton(pub String); // Stores component type name
 
+/// The inspector's add component button, or the component search while adding one.
+#[derive(Component)]
+pub struct AddComponentSection;
+
+/// The search field's [`TextField`](super::text_field::TextField) holds the filter.
 #[derive(Component)]
 pub struct ComponentSearchInput;
 
+#[derive(Component)]
+pub struct ComponentSearchResults;
+
 #[derive(Component)]
 pub struct ComponentAddButton(pub String); // Stores component name to add
 
+#[derive(Component)]
+pub struct RevertOverrideButton {
+    pub entity: Entity,
+    pub component: String,
+    pub field: String,
+}
+
 #[derive(Component)]
 pub struct PropertyInput {
     pub entity: Entity,
     pub component_type_id: std::any::TypeId,
     pub field_name: String,
-    pub current_value: String,
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq)]
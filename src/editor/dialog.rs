@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use std::path::PathBuf;
+use super::autosave::{remove_recovery_file, AutosaveState};
+use super::menu::{guard_unsaved_changes, GuardedAction, LoadRequest, PendingAction, SaveRequest, SceneInfo};
+use super::file_dialog::{FileBrowser, FileDialogKind, FileDialogRequest, FilePurpose, OpenFileDialog};
+use super::templates::SceneTemplate;
+use super::save_filter::EditorOnly;
+use super::styles::*;
+
+/// Root of a modal dialog. It covers the whole window so nothing behind it can be clicked.
+#[derive(Component, Default)]
+#[require(EditorOnly)]
+pub struct ModalDialog;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct DialogButton(pub DialogChoice);
+
+/// What happens when a dialog button is pressed. Every choice closes the dialog.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DialogChoice {
+    /// Load the recovery file in place of the scene.
+    RestoreRecovery(PathBuf),
+    /// Delete the recovery file.
+    DiscardRecovery(PathBuf),
+    /// Save the scene, then run the action.
+    SaveThen(GuardedAction),
+    /// Drop the unsaved changes and run the action.
+    DiscardThen(GuardedAction),
+    /// Replace the scene with a new one from the template, asking about unsaved changes first.
+    NewScene(SceneTemplate),
+    /// Load the open scene's file again, dropping unsaved changes.
+    ReloadScene,
+    Cancel,
+}
+
+/// Spawns a modal dialog with a message and one button per choice.
+pub fn spawn_dialog(commands: &mut Commands, message: &str, buttons: Vec<(String, DialogChoice)>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        GlobalZIndex(100),
+        FocusPolicy::Block,
+        ModalDialog,
+    )).with_children(|overlay| {
+        overlay.spawn((
+            Node {
+                max_width: Val::Px(420.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(12.0)),
+                row_gap: Val::Px(12.0),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_COLOR),
+            BorderColor::all(RESIZE_HANDLE_COLOR),
+        )).with_children(|panel| {
+            panel.spawn((
+                Text::new(message),
+                TextFont::default(),
+                TextColor(TEXT_COLOR),
+            ));
+            panel.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::FlexEnd,
+                column_gap: Val::Px(8.0),
+                ..default()
+            }).with_children(|row| {
+                for (label, choice) in buttons {
+                    row.spawn((
+                        Button,
+                        menu_button_style(),
+                        BackgroundColor(BUTTON_COLOR_NORMAL),
+                        DialogButton(choice),
+                    )).with_children(|btn| {
+                        btn.spawn((
+                            Text::new(label),
+                            TextFont::default(),
+                            TextColor(TEXT_COLOR),
+                        ));
+                    });
+                }
+            });
+        });
+    });
+}
+
+pub fn dialog_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &DialogButton),
+        Changed<Interaction>,
+    >,
+    dialogs: Query<Entity, (With<ModalDialog>, Without<FileBrowser>)>,
+    mut commands: Commands,
+    scene_info: Res<SceneInfo>,
+    mut autosave: ResMut<AutosaveState>,
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BUTTON_COLOR_PRESSED.into();
+                for dialog in dialogs.iter() {
+                    commands.entity(dialog).despawn();
+                }
+
+                match &button.0 {
+                    DialogChoice::RestoreRecovery(path) => {
+                        commands.insert_resource(LoadRequest { source: Some(path.clone()) });
+                    }
+                    DialogChoice::DiscardRecovery(path) => {
+                        remove_recovery_file(path);
+                    }
+                    DialogChoice::SaveThen(action) => {
+                        if scene_info.file_path.is_none() {
+                            commands.queue(OpenFileDialog(FileDialogRequest::new(
+                                FileDialogKind::Save,
+                                FilePurpose::SaveScene { then: Some(action.clone()) },
+                            )));
+                            continue;
+                        }
+                        commands.insert_resource(SaveRequest);
+                        commands.insert_resource(PendingAction { action: action.clone(), discard_changes: false });
+                    }
+                    DialogChoice::DiscardThen(action) => {
+                        autosave.remove_recovery_files(scene_info.file_path.as_deref());
+                        commands.insert_resource(PendingAction { action: action.clone(), discard_changes: true });
+                    }
+                    DialogChoice::NewScene(template) => {
+                        // This dialog is closing, so the unsaved-changes prompt may replace it
+                        guard_unsaved_changes(&mut commands, &scene_info, false, GuardedAction::NewScene(template.clone()));
+                    }
+                    DialogChoice::ReloadScene => {
+                        commands.insert_resource(LoadRequest::default());
+                    }
+                    DialogChoice::Cancel => {}
+                }
+            }
+            Interaction::Hovered => {
+                *color = BUTTON_COLOR_HOVER.into();
+            }
+            Interaction::None => {
+                *color = BUTTON_COLOR_NORMAL.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::ui::FocusPolicy;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use super::dialog::ModalDialog;
+use super::input::TypedText;
+use super::menu::GuardedAction;
+use super::resources::EditorState;
+use super::scene_format::BINARY_SCENE_EXTENSION;
+use super::styles::*;
+use super::text_field::{text_field_text, TextField};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileDialogKind {
+    Open,
+    Save,
+}
+
+/// What the picked file is for. Carried through the dialog and handed back with the answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilePurpose {
+    /// Save the scene to the file, then run `then` if given.
+    SaveScene { then: Option<GuardedAction> },
+    LoadScene,
+    /// Import the scene, under a new parent entity if `under_parent`.
+    ImportScene { under_parent: bool },
+    /// Save the entity and its descendants as a prefab.
+    SavePrefab(Entity),
+    InstantiatePrefab,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileDialogRequest {
+    pub kind: FileDialogKind,
+    pub purpose: FilePurpose,
+    pub title: String,
+    pub filter_name: String,
+    /// Extensions without the leading dot, e.g. `scn.ron`.
+    pub extensions: Vec<String>,
+    pub directory: PathBuf,
+}
+
+impl FileDialogRequest {
+    /// A request for scene or prefab files starting in the working directory.
+    pub fn new(kind: FileDialogKind, purpose: FilePurpose) -> Self {
+        // Scenes can be RON or binary; saves pick the format from the extension
+        let scene = ["scn.ron", BINARY_SCENE_EXTENSION].as_slice();
+        let (title, filter_name, extensions) = match &purpose {
+            FilePurpose::SaveScene { .. } => ("Save Scene", "Scene", scene),
+            FilePurpose::LoadScene => ("Load Scene", "Scene", scene),
+            FilePurpose::ImportScene { under_parent: false } => ("Import Scene", "Scene", scene),
+            FilePurpose::ImportScene { under_parent: true } => ("Import Scene Under Parent", "Scene", scene),
+            FilePurpose::SavePrefab(_) => ("Save Prefab", "Prefab", ["prefab.ron"].as_slice()),
+            FilePurpose::InstantiatePrefab => ("Instantiate Prefab", "Prefab", ["prefab.ron"].as_slice()),
+        };
+        Self {
+            kind,
+            purpose,
+            title: title.to_string(),
+            filter_name: filter_name.to_string(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            directory: std::env::current_dir().unwrap_or_default(),
+        }
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        self.extensions.iter().any(|ext| name.ends_with(&format!(".{}", ext)))
+    }
+
+    /// `path` with the first extension appended unless it already has one of them.
+    pub fn with_extension(&self, path: PathBuf) -> PathBuf {
+        match self.extensions.first() {
+            Some(ext) if !self.matches(&path) => {
+                let mut name = path.file_name().unwrap_or_default().to_os_string();
+                name.push(format!(".{}", ext));
+                path.with_file_name(name)
+            }
+            _ => path,
+        }
+    }
+}
+
+/// The answer to a [`FileDialogRequest`]; `path` is `None` when the dialog was cancelled.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct FileDialogResult {
+    pub purpose: FilePurpose,
+    pub path: Option<PathBuf>,
+}
+
+/// Shows file dialogs without blocking the frame. Answers are collected by [`poll_file_dialogs`]
+/// and sent as [`FileDialogResult`] messages.
+pub trait FileDialogProvider: Send + Sync + 'static {
+    fn open(&mut self, request: FileDialogRequest, world: &mut World);
+    /// Answers that arrived since the last call.
+    fn poll(&mut self, world: &mut World) -> Vec<FileDialogResult>;
+}
+
+/// The [`FileDialogProvider`] used by the editor.
+#[derive(Resource)]
+pub struct FileDialogs(pub Box<dyn FileDialogProvider>);
+
+/// Which [`FileDialogProvider`] [`EditorPlugin`](super::EditorPlugin) installs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileDialogBackend {
+    /// The platform's dialogs through `rfd`.
+    #[default]
+    Native,
+    /// [`FileBrowserDialogs`], for systems without a native dialog.
+    InEditor,
+}
+
+impl FileDialogBackend {
+    pub fn provider(self) -> FileDialogs {
+        match self {
+            FileDialogBackend::Native => FileDialogs(Box::new(RfdFileDialogs::default())),
+            FileDialogBackend::InEditor => FileDialogs(Box::new(FileBrowserDialogs)),
+        }
+    }
+}
+
+/// Asks the editor's [`FileDialogs`] for a file.
+pub struct OpenFileDialog(pub FileDialogRequest);
+
+impl bevy::ecs::system::Command for OpenFileDialog {
+    fn apply(self, world: &mut World) {
+        if !world.contains_resource::<FileDialogs>() {
+            warn!("No file dialog provider; ignoring {:?}", self.0.title);
+            return;
+        }
+        world.resource_scope(|world, mut dialogs: Mut<FileDialogs>| dialogs.0.open(self.0, world));
+    }
+}
+
+pub fn poll_file_dialogs(world: &mut World) {
+    if !world.contains_resource::<FileDialogs>() {
+        return;
+    }
+    let results = world.resource_scope(|world, mut dialogs: Mut<FileDialogs>| dialogs.0.poll(world));
+    for result in results {
+        world.write_message(result);
+    }
+}
+
+/// Native dialogs from `rfd`, run on the async compute pool.
+#[derive(Default)]
+pub struct RfdFileDialogs {
+    tasks: Vec<(FilePurpose, Task<Option<PathBuf>>)>,
+}
+
+impl FileDialogProvider for RfdFileDialogs {
+    fn open(&mut self, request: FileDialogRequest, _world: &mut World) {
+        let extensions: Vec<&str> = request.extensions.iter().map(String::as_str).collect();
+        let dialog = rfd::AsyncFileDialog::new()
+            .set_title(&request.title)
+            .set_directory(&request.directory)
+            .add_filter(&request.filter_name, &extensions);
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let file = match request.kind {
+                FileDialogKind::Open => dialog.pick_file().await,
+                FileDialogKind::Save => dialog.save_file().await,
+            };
+            file.map(|file| file.path().to_path_buf())
+        });
+        self.tasks.push((request.purpose, task));
+    }
+
+    fn poll(&mut self, _world: &mut World) -> Vec<FileDialogResult> {
+        let mut results = Vec::new();
+        self.tasks.retain_mut(|(purpose, task)| match block_on(poll_once(task)) {
+            Some(path) => {
+                results.push(FileDialogResult { purpose: purpose.clone(), path });
+                false
+            }
+            None => true,
+        });
+        results
+    }
+}
+
+/// Answers requests from a script instead of asking anyone, for tests.
+#[derive(Default)]
+pub struct ScriptedFileDialogs {
+    /// Paths handed out in order; `None` cancels. Once empty, every dialog is cancelled.
+    pub answers: VecDeque<Option<PathBuf>>,
+    pending: Vec<FileDialogResult>,
+}
+
+impl ScriptedFileDialogs {
+    pub fn new(answers: impl IntoIterator<Item = Option<PathBuf>>) -> Self {
+        Self {
+            answers: answers.into_iter().collect(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl FileDialogProvider for ScriptedFileDialogs {
+    fn open(&mut self, request: FileDialogRequest, _world: &mut World) {
+        let path = self.answers.pop_front().flatten();
+        self.pending.push(FileDialogResult { purpose: request.purpose, path });
+    }
+
+    fn poll(&mut self, _world: &mut World) -> Vec<FileDialogResult> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Dialogs drawn with Bevy UI: a modal listing the current directory above a file name field.
+pub struct FileBrowserDialogs;
+
+/// State of an open file browser, on its root entity.
+#[derive(Component, Debug)]
+#[require(ModalDialog)]
+pub struct FileBrowser {
+    pub request: FileDialogRequest,
+    pub directory: PathBuf,
+    /// Directory the entry list currently shows.
+    pub listed: Option<PathBuf>,
+    /// Set once the user confirmed or cancelled; `Some(None)` is a cancel.
+    pub answer: Option<Option<PathBuf>>,
+}
+
+#[derive(Component)]
+pub struct FileBrowserList;
+
+#[derive(Component)]
+pub struct FileBrowserDirectoryText;
+
+/// The file name [`TextField`], which keeps focus while its browser is open.
+#[derive(Component)]
+pub struct FileBrowserNameInput;
+
+#[derive(Component, Clone, Debug)]
+pub enum FileBrowserButton {
+    Entry(PathBuf),
+    Confirm,
+    Cancel,
+}
+
+impl FileDialogProvider for FileBrowserDialogs {
+    fn open(&mut self, request: FileDialogRequest, world: &mut World) {
+        let kind = request.kind;
+        let title = request.title.clone();
+        let directory = request.directory.clone();
+        world.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            GlobalZIndex(100),
+            FocusPolicy::Block,
+            FileBrowser {
+                request,
+                directory,
+                listed: None,
+                answer: None,
+            },
+        )).with_children(|overlay| {
+            overlay.spawn((
+                Node {
+                    width: Val::Px(480.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    row_gap: Val::Px(8.0),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BackgroundColor(PANEL_COLOR),
+                BorderColor::all(RESIZE_HANDLE_COLOR),
+            )).with_children(|panel| {
+                panel.spawn((Text::new(title), TextFont::default(), TextColor(TEXT_COLOR)));
+                panel.spawn((
+                    Text::new(""),
+                    TextFont { font_size: 12.0, ..default() },
+                    TextColor(TEXT_COLOR),
+                    FileBrowserDirectoryText,
+                ));
+                panel.spawn((
+                    Node {
+                        height: Val::Px(240.0),
+                        flex_direction: FlexDirection::Column,
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    BackgroundColor(BACKGROUND_COLOR),
+                    ScrollPosition::default(),
+                    FileBrowserList,
+                ));
+                panel.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                }).with_children(|row| {
+                    row.spawn((Text::new("File name:"), TextFont::default(), TextColor(TEXT_COLOR)));
+                    row.spawn((
+                        Button,
+                        Node {
+                            flex_grow: 1.0,
+                            padding: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        BackgroundColor(BACKGROUND_COLOR),
+                        FileBrowserNameInput,
+                        TextField::new(""),
+                        children![text_field_text(14.0)],
+                    ));
+                });
+                panel.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::FlexEnd,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                }).with_children(|row| {
+                    let confirm = match kind {
+                        FileDialogKind::Open => "Open",
+                        FileDialogKind::Save => "Save",
+                    };
+                    for (label, button) in [(confirm, FileBrowserButton::Confirm), ("Cancel", FileBrowserButton::Cancel)] {
+                        row.spawn((
+                            Button,
+                            menu_button_style(),
+                            BackgroundColor(BUTTON_COLOR_NORMAL),
+                            button,
+                        )).with_children(|btn| {
+                            btn.spawn((Text::new(label), TextFont::default(), TextColor(TEXT_COLOR)));
+                        });
+                    }
+                });
+            });
+        });
+    }
+
+    fn poll(&mut self, world: &mut World) -> Vec<FileDialogResult> {
+        let mut browsers = world.query::<(Entity, &FileBrowser)>();
+        let answered: Vec<(Entity, FileDialogResult)> = browsers
+            .iter(world)
+            .filter_map(|(entity, browser)| {
+                browser.answer.clone().map(|path| {
+                    (entity, FileDialogResult { purpose: browser.request.purpose.clone(), path })
+                })
+            })
+            .collect();
+        answered
+            .into_iter()
+            .map(|(entity, result)| {
+                world.despawn(entity);
+                result
+            })
+            .collect()
+    }
+}
+
+/// Directories first, then files matching the request, both sorted by name.
+pub fn list_directory(request: &FileDialogRequest, directory: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir() || request.matches(path))
+        .collect();
+    entries.sort_by_key(|path| (!path.is_dir(), path.file_name().map(|n| n.to_ascii_lowercase())));
+    entries
+}
+
+/// Refreshes a file browser's directory text, and its entry list when it opens or changes directory.
+pub fn update_file_browser_list(
+    mut commands: Commands,
+    mut browsers: Query<(&mut FileBrowser, &Children), Changed<FileBrowser>>,
+    children_query: Query<&Children>,
+    mut lists: Query<(Entity, &mut ScrollPosition), With<FileBrowserList>>,
+    mut texts: Query<&mut Text, With<FileBrowserDirectoryText>>,
+) {
+    for (mut browser, children) in browsers.iter_mut() {
+        for descendant in children.iter().flat_map(|child| std::iter::once(child).chain(children_query.iter_descendants(child))) {
+            if let Ok(mut text) = texts.get_mut(descendant) {
+                **text = browser.directory.to_string_lossy().into_owned();
+            }
+            let Ok((list, mut scroll)) = lists.get_mut(descendant) else {
+                continue;
+            };
+            if browser.listed.as_ref() == Some(&browser.directory) {
+                continue;
+            }
+            browser.listed = Some(browser.directory.clone());
+            scroll.0 = Vec2::ZERO;
+
+            let parent = browser.directory.parent().map(Path::to_path_buf);
+            let entries = list_directory(&browser.request, &browser.directory);
+            commands.entity(list).despawn_related::<Children>().with_children(|list| {
+                for (label, path) in parent
+                    .map(|p| ("..".to_string(), p))
+                    .into_iter()
+                    .chain(entries.into_iter().map(|path| {
+                        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        let label = if path.is_dir() { format!("{}/", name) } else { name };
+                        (label, path)
+                    }))
+                {
+                    list.spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                            flex_shrink: 0.0,
+                            ..default()
+                        },
+                        BackgroundColor(BACKGROUND_COLOR),
+                        FileBrowserButton::Entry(path),
+                    )).with_children(|btn| {
+                        btn.spawn((Text::new(label), TextFont::default(), TextColor(TEXT_COLOR)));
+                    });
+                }
+            });
+        }
+    }
+}
+
+/// Clicks, confirming and scrolling in open file browsers. The file name is typed into its
+/// [`TextField`] by [`text_input_system`](super::input::text_input_system).
+#[allow(clippy::too_many_arguments)]
+pub fn file_browser_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &FileBrowserButton),
+        Changed<Interaction>,
+    >,
+    mut browsers: Query<&mut FileBrowser>,
+    mut names: Query<(Entity, &mut TextField), With<FileBrowserNameInput>>,
+    mut lists: Query<&mut ScrollPosition, With<FileBrowserList>>,
+    mut editor_state: ResMut<EditorState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    typed: Res<TypedText>,
+    mut wheel: MessageReader<MouseWheel>,
+) {
+    let Some(mut browser) = browsers.iter_mut().find(|b| b.answer.is_none()) else {
+        wheel.clear();
+        return;
+    };
+    let Ok((name_input, mut name)) = names.single_mut() else {
+        wheel.clear();
+        return;
+    };
+    // Clicking the list drops focus; typing should still go to the name
+    if editor_state.focused_input != Some(name_input) {
+        editor_state.focused_input = Some(name_input);
+    }
+
+    let mut confirmed = typed.submitted();
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        let normal = match button {
+            FileBrowserButton::Entry(_) => BACKGROUND_COLOR,
+            _ => BUTTON_COLOR_NORMAL,
+        };
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BUTTON_COLOR_PRESSED.into();
+                match button {
+                    FileBrowserButton::Entry(path) if path.is_dir() => {
+                        browser.directory = path.clone();
+                    }
+                    FileBrowserButton::Entry(path) => {
+                        name.set_text(path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+                    }
+                    FileBrowserButton::Confirm => confirmed = true,
+                    FileBrowserButton::Cancel => browser.answer = Some(None),
+                }
+            }
+            Interaction::Hovered => {
+                *color = BUTTON_COLOR_HOVER.into();
+            }
+            Interaction::None => {
+                *color = normal.into();
+            }
+        }
+    }
+
+    if confirmed && let Some(path) = confirm(&browser, &name.text) {
+        browser.answer = Some(Some(path));
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        browser.answer = Some(None);
+    }
+
+    for event in wheel.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y * 20.0,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for mut scroll in lists.iter_mut() {
+            scroll.0.y = (scroll.0.y - lines).max(0.0);
+        }
+    }
+}
+
+/// The file `name` picks in the browser's directory, if it can be opened or saved to.
+fn confirm(browser: &FileBrowser, name: &str) -> Option<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let path = browser.directory.join(name);
+    match browser.request.kind {
+        FileDialogKind::Open if path.is_file() => Some(path),
+        FileDialogKind::Open => {
+            warn!("No such file: {:?}", path);
+            None
+        }
+        FileDialogKind::Save => Some(browser.request.with_extension(path)),
+    }
+}
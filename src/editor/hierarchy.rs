@@ -1,15 +1,30 @@
 use bevy::prelude::*;
 use super::styles::*;
 use super::components::*;
+use super::camera::FrameEntity;
 use super::resources::EditorState;
 
+/// Longest gap between the clicks of a double click, in seconds.
+const DOUBLE_CLICK_SECS: f64 = 0.4;
+
+/// Selects the clicked row's entity. Double clicking a row also frames it in the viewport.
 pub fn update_hierarchy(
+    mut commands: Commands,
     mut current_state: ResMut<EditorState>,
+    time: Res<Time<Real>>,
+    mut last_click: Local<Option<(Entity, f64)>>,
     interactions: Query<(&Interaction, &HierarchyEntityRow), Changed<Interaction>>,
 ) {
     for (interaction, row) in interactions.iter() {
         if *interaction == Interaction::Pressed {
             current_state.selected_entity = Some(row.entity);
+            let now = time.elapsed_secs_f64();
+            if matches!(*last_click, Some((entity, at)) if entity == row.entity && now - at <= DOUBLE_CLICK_SECS) {
+                commands.queue(FrameEntity(row.entity));
+                *last_click = None;
+            } else {
+                *last_click = Some((row.entity, now));
+            }
         }
     }
 }
@@ -1,117 +1,373 @@
 use bevy::prelude::*;
-use super::resources::InspectorUiState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::ecs::system::SystemParam;
+use bevy::window::{Ime, PrimaryWindow};
+use super::components::{ComponentSearchInput, InspectorPanel, PropertyInput};
+use super::file_dialog::FileBrowser;
+use super::keymap::{EditorAction, EditorKeymap};
+use super::resources::{EditorState, InspectorUiState};
+use super::text_field::{CaretMotion, EditorClipboard, TextField};
 
-type PropertyInputQuery<'w, 's> = Query<'w, 's, (Entity, &'static Interaction), (Changed<Interaction>, With<super::components::PropertyInput>)>;
+type PressedTextFieldQuery<'w, 's> = Query<'w, 's, (Entity, &'static Interaction, Has<PropertyInput>), (Changed<Interaction>, With<TextField>)>;
+type TextFieldQuery<'w, 's> = Query<'w, 's, (&'static mut TextField, Option<&'static PropertyInput>, Has<ComponentSearchInput>)>;
 
+/// The inspector's [`PropertyInput`]s in the order they are shown.
+#[derive(SystemParam)]
+pub struct PropertyTabOrder<'w, 's> {
+    panels: Query<'w, 's, Entity, With<InspectorPanel>>,
+    children: Query<'w, 's, &'static Children>,
+    property_inputs: Query<'w, 's, (), With<PropertyInput>>,
+}
+
+impl PropertyTabOrder<'_, '_> {
+    pub fn get(&self) -> Vec<Entity> {
+        self.panels
+            .iter()
+            .flat_map(|panel| self.children.iter_descendants_depth_first(panel))
+            .filter(|&entity| self.property_inputs.contains(entity))
+            .collect()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn text_input_system(
     mut commands: Commands,
-    keys: Res<ButtonInput<KeyCode>>,
+    typed: Res<TypedText>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut clipboard: ResMut<EditorClipboard>,
     mut ui_state: ResMut<InspectorUiState>,
-    mut editor_state: ResMut<super::resources::EditorState>,
-    query: PropertyInputQuery,
-    mut property_inputs: Query<(&mut super::components::PropertyInput, &Children)>,
-    mut text_query: Query<&mut Text>,
+    mut editor_state: ResMut<EditorState>,
+    pressed: PressedTextFieldQuery,
+    mut fields: TextFieldQuery,
+    tab_order: PropertyTabOrder,
 ) {
-    // 1. Handle Focus Selection
-    for (entity, interaction) in query.iter() {
-        if *interaction == Interaction::Pressed {
+    // 1. Handle Focus Selection; clicking anywhere else commits the focused field
+    let clicked = pressed
+        .iter()
+        .find(|(_, interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(entity, _, is_property)| (entity, is_property));
+    if clicked.is_some() || mouse.just_pressed(MouseButton::Left) {
+        if let Some(focused) = editor_state.focused_input
+            && clicked.map(|(entity, _)| entity) != Some(focused)
+        {
+            if let Ok((mut field, property, _)) = fields.get_mut(focused) {
+                commit(&mut commands, &mut field, property);
+            }
+            editor_state.focused_input = None;
+        }
+        if let Some((entity, is_property)) = clicked {
             editor_state.focused_input = Some(entity);
             // Close component search if we are editing properties
-            ui_state.is_adding_component = false;
+            if is_property && ui_state.is_adding_component {
+                ui_state.is_adding_component = false;
+            }
         }
     }
 
-    // Helper to map keys to chars (Fallback implementation)
-    let mut pushed_chars = Vec::new();
-    // A-Z
-    if keys.just_pressed(KeyCode::KeyA) { pushed_chars.push('a'); }
-    if keys.just_pressed(KeyCode::KeyB) { pushed_chars.push('b'); }
-    if keys.just_pressed(KeyCode::KeyC) { pushed_chars.push('c'); }
-    if keys.just_pressed(KeyCode::KeyD) { pushed_chars.push('d'); }
-    if keys.just_pressed(KeyCode::KeyE) { pushed_chars.push('e'); }
-    if keys.just_pressed(KeyCode::KeyF) { pushed_chars.push('f'); }
-    if keys.just_pressed(KeyCode::KeyG) { pushed_chars.push('g'); }
-    if keys.just_pressed(KeyCode::KeyH) { pushed_chars.push('h'); }
-    if keys.just_pressed(KeyCode::KeyI) { pushed_chars.push('i'); }
-    if keys.just_pressed(KeyCode::KeyJ) { pushed_chars.push('j'); }
-    if keys.just_pressed(KeyCode::KeyK) { pushed_chars.push('k'); }
-    if keys.just_pressed(KeyCode::KeyL) { pushed_chars.push('l'); }
-    if keys.just_pressed(KeyCode::KeyM) { pushed_chars.push('m'); }
-    if keys.just_pressed(KeyCode::KeyN) { pushed_chars.push('n'); }
-    if keys.just_pressed(KeyCode::KeyO) { pushed_chars.push('o'); }
-    if keys.just_pressed(KeyCode::KeyP) { pushed_chars.push('p'); }
-    if keys.just_pressed(KeyCode::KeyQ) { pushed_chars.push('q'); }
-    if keys.just_pressed(KeyCode::KeyR) { pushed_chars.push('r'); }
-    if keys.just_pressed(KeyCode::KeyS) { pushed_chars.push('s'); }
-    if keys.just_pressed(KeyCode::KeyT) { pushed_chars.push('t'); }
-    if keys.just_pressed(KeyCode::KeyU) { pushed_chars.push('u'); }
-    if keys.just_pressed(KeyCode::KeyV) { pushed_chars.push('v'); }
-    if keys.just_pressed(KeyCode::KeyW) { pushed_chars.push('w'); }
-    if keys.just_pressed(KeyCode::KeyX) { pushed_chars.push('x'); }
-    if keys.just_pressed(KeyCode::KeyY) { pushed_chars.push('y'); }
-    if keys.just_pressed(KeyCode::KeyZ) { pushed_chars.push('z'); }
-    // 0-9
-    if keys.just_pressed(KeyCode::Digit0) { pushed_chars.push('0'); }
-    if keys.just_pressed(KeyCode::Digit1) { pushed_chars.push('1'); }
-    if keys.just_pressed(KeyCode::Digit2) { pushed_chars.push('2'); }
-    if keys.just_pressed(KeyCode::Digit3) { pushed_chars.push('3'); }
-    if keys.just_pressed(KeyCode::Digit4) { pushed_chars.push('4'); }
-    if keys.just_pressed(KeyCode::Digit5) { pushed_chars.push('5'); }
-    if keys.just_pressed(KeyCode::Digit6) { pushed_chars.push('6'); }
-    if keys.just_pressed(KeyCode::Digit7) { pushed_chars.push('7'); }
-    if keys.just_pressed(KeyCode::Digit8) { pushed_chars.push('8'); }
-    if keys.just_pressed(KeyCode::Digit9) { pushed_chars.push('9'); }
-    // Misc
-    if keys.just_pressed(KeyCode::Space) { pushed_chars.push(' '); }
-    if keys.just_pressed(KeyCode::Period) { pushed_chars.push('.'); }
-    if keys.just_pressed(KeyCode::Minus) { pushed_chars.push('-'); }
-
-    // 2. Handle Input for Focused Property
-    if let Some(focused) = editor_state.focused_input {
-        if let Ok((mut prop_input, children)) = property_inputs.get_mut(focused) {
-             let mut changed = false;
-
-             for char in &pushed_chars {
-                 prop_input.current_value.push(*char);
-                 changed = true;
-             }
-
-             if keys.just_pressed(KeyCode::Backspace) {
-                 prop_input.current_value.pop();
-                 changed = true;
-             }
-
-             if keys.just_pressed(KeyCode::Enter) {
-                  commands.queue(super::actions::PropertyChangeCommand {
-                      entity: prop_input.entity,
-                      component_type_id: prop_input.component_type_id,
-                      field_name: prop_input.field_name.clone(),
-                      new_value: prop_input.current_value.clone(),
-                  });
-             }
-
-             if changed
-                 && let Some(text_entity) = children.first()
-                 && let Ok(mut text) = text_query.get_mut(*text_entity) {
-                     **text = prop_input.current_value.clone();
-             }
-             return;
-        } else {
-             // Focused entity probably despawned or error
-             editor_state.focused_input = None;
+    // 2. Handle Input for the Focused Field
+    let Some(focused) = editor_state.focused_input else {
+        return;
+    };
+    let Ok((mut field, property, is_search)) = fields.get_mut(focused) else {
+        // Focused entity probably despawned or error
+        editor_state.focused_input = None;
+        return;
+    };
+    if typed.0.is_empty() {
+        return;
+    }
+
+    let mut changed = false;
+    for edit in &typed.0 {
+        match edit {
+            TextEdit::Submit => {
+                commit(&mut commands, &mut field, property);
+                editor_state.focused_input = None;
+                break;
+            }
+            TextEdit::Cancel => {
+                changed |= field.revert();
+                if is_search {
+                    ui_state.is_adding_component = false;
+                }
+                editor_state.focused_input = None;
+                break;
+            }
+            TextEdit::FocusNext { backwards } => {
+                commit(&mut commands, &mut field, property);
+                editor_state.focused_input = next_in_order(&tab_order.get(), focused, *backwards);
+                break;
+            }
+            edit => changed |= field.apply(edit, clipboard.0.as_mut()),
         }
     }
 
-    // 3. Handle Component Search Input (Fallback)
-    if !ui_state.is_adding_component {
+    if is_search && changed {
+        ui_state.component_filter = field.text.clone();
+    }
+}
+
+/// Writes an edited property field back to its component.
+fn commit(commands: &mut Commands, field: &mut TextField, property: Option<&PropertyInput>) {
+    field.anchor = None;
+    let Some(prop_input) = property else {
+        return;
+    };
+    if field.text == field.original {
         return;
     }
+    field.original = field.text.clone();
+    commands.queue(super::actions::PropertyChangeCommand {
+        entity: prop_input.entity,
+        component_type_id: prop_input.component_type_id,
+        field_name: prop_input.field_name.clone(),
+        new_value: field.text.clone(),
+    });
+}
+
+/// The entity after (or before) `current` in `order`, wrapping around. Starts at an end when
+/// `current` isn't in `order`.
+pub fn next_in_order(order: &[Entity], current: Entity, backwards: bool) -> Option<Entity> {
+    let len = order.len();
+    if len == 0 {
+        return None;
+    }
+    let index = match (order.iter().position(|&e| e == current), backwards) {
+        (Some(i), false) => (i + 1) % len,
+        (Some(i), true) => (i + len - 1) % len,
+        (None, false) => 0,
+        (None, true) => len - 1,
+    };
+    Some(order[index])
+}
+
+/// One change typed into a text field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextEdit {
+    /// Text produced by the keyboard layout (including Shift, AltGr and dead keys) or committed
+    /// by an input method.
+    Insert(String),
+    Backspace,
+    Delete,
+    Move { motion: CaretMotion, select: bool },
+    SelectAll,
+    Cut,
+    Copy,
+    Paste,
+    Submit,
+    /// Escape: drop the edit.
+    Cancel,
+    /// Tab, or Shift+Tab when `backwards`.
+    FocusNext { backwards: bool },
+}
+
+/// Everything typed this frame, in order. Text fields read it instead of raw key codes.
+#[derive(Resource, Default, Debug)]
+pub struct TypedText(pub Vec<TextEdit>);
+
+impl TypedText {
+    /// Applies this frame's insertions and backspaces to the end of `text`, for inputs without a
+    /// [`TextField`]. Returns whether it changed.
+    pub fn apply(&self, text: &mut String) -> bool {
+        let mut changed = false;
+        for edit in &self.0 {
+            match edit {
+                TextEdit::Insert(inserted) => {
+                    text.push_str(inserted);
+                    changed = true;
+                }
+                TextEdit::Backspace => changed |= text.pop().is_some(),
+                _ => {}
+            }
+        }
+        changed
+    }
+
+    pub fn submitted(&self) -> bool {
+        self.0.contains(&TextEdit::Submit)
+    }
+}
+
+/// Keys bound in the [`EditorKeymap`] that edit text rather than insert it.
+const TEXT_ACTIONS: [EditorAction; 5] = [
+    EditorAction::TextSubmit,
+    EditorAction::TextCancel,
+    EditorAction::TextBackspace,
+    EditorAction::TextDelete,
+    EditorAction::TextNextField,
+];
+
+/// Fills [`TypedText`] from this frame's keyboard and IME messages. Held keys repeat.
+pub fn collect_typed_text(
+    mut typed: ResMut<TypedText>,
+    keys: Res<ButtonInput<KeyCode>>,
+    keymap: Res<EditorKeymap>,
+    mut keyboard: MessageReader<KeyboardInput>,
+    mut ime: MessageReader<Ime>,
+) {
+    typed.0.clear();
+    let command = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]);
+    let select = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for event in keyboard.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let motion = |motion| TextEdit::Move { motion, select };
+        if let Some(action) = keymap.action_for(&TEXT_ACTIONS, event.key_code, &keys) {
+            typed.0.push(match action {
+                EditorAction::TextSubmit => TextEdit::Submit,
+                EditorAction::TextCancel => TextEdit::Cancel,
+                EditorAction::TextBackspace => TextEdit::Backspace,
+                EditorAction::TextDelete => TextEdit::Delete,
+                _ => TextEdit::FocusNext { backwards: select },
+            });
+            continue;
+        }
+        match &event.logical_key {
+            Key::ArrowLeft => typed.0.push(motion(CaretMotion::Left)),
+            Key::ArrowRight => typed.0.push(motion(CaretMotion::Right)),
+            Key::Home => typed.0.push(motion(CaretMotion::Home)),
+            Key::End => typed.0.push(motion(CaretMotion::End)),
+            _ if command => {
+                // Ctrl (Cmd on macOS) shortcuts never insert text
+                match shortcut_letter(event) {
+                    Some('a') => typed.0.push(TextEdit::SelectAll),
+                    Some('x') => typed.0.push(TextEdit::Cut),
+                    Some('c') => typed.0.push(TextEdit::Copy),
+                    Some('v') => typed.0.push(TextEdit::Paste),
+                    _ => {}
+                }
+            }
+            _ => {
+                // `text` is what the layout produces; control keys like Tab and Escape produce control characters
+                let text: String = event.text.iter().flat_map(|t| t.chars()).filter(|c| !c.is_control()).collect();
+                if !text.is_empty() {
+                    typed.0.push(TextEdit::Insert(text));
+                }
+            }
+        }
+    }
+    for event in ime.read() {
+        if let Ime::Commit { value, .. } = event {
+            typed.0.push(TextEdit::Insert(value.clone()));
+        }
+    }
+}
+
+/// Keyboard state left over after the focused text field took its keys. Camera controls and
+/// editor shortcuts read this instead of `ButtonInput<KeyCode>`.
+#[derive(Resource, Default, Debug)]
+pub struct EditorKeys(pub ButtonInput<KeyCode>);
+
+/// Keys a text field keeps for itself while focused. Modifiers and function keys still reach
+/// shortcuts.
+pub fn is_text_key(key: KeyCode) -> bool {
+    !matches!(
+        key,
+        KeyCode::ShiftLeft | KeyCode::ShiftRight | KeyCode::ControlLeft | KeyCode::ControlRight
+            | KeyCode::AltLeft | KeyCode::AltRight | KeyCode::SuperLeft | KeyCode::SuperRight
+            | KeyCode::F1 | KeyCode::F2 | KeyCode::F3 | KeyCode::F4 | KeyCode::F5 | KeyCode::F6
+            | KeyCode::F7 | KeyCode::F8 | KeyCode::F9 | KeyCode::F10 | KeyCode::F11 | KeyCode::F12
+    )
+}
+
+/// Copies the keyboard into [`EditorKeys`], leaving out the keys a focused text field or the file
+/// browser consumes. Keys held when typing starts are released.
+pub fn route_keyboard_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    editor_state: Res<EditorState>,
+    browsers: Query<(), With<FileBrowser>>,
+    mut routed: ResMut<EditorKeys>,
+) {
+    let typing = editor_state.focused_input.is_some() || !browsers.is_empty();
+    let forwarded = |key: KeyCode| !(typing && is_text_key(key));
+
+    routed.0.clear();
+    // Just pressed as well, for keys tapped within one frame
+    for &key in keys.get_pressed().chain(keys.get_just_pressed()) {
+        if forwarded(key) {
+            routed.0.press(key);
+        }
+    }
+    let released: Vec<KeyCode> = routed
+        .0
+        .get_pressed()
+        .copied()
+        .filter(|&key| !keys.pressed(key) || !forwarded(key))
+        .collect();
+    for key in released {
+        routed.0.release(key);
+    }
+}
+
+/// The letter of a shortcut key: the layout's letter if it is a Latin one, else the physical key's.
+fn shortcut_letter(event: &KeyboardInput) -> Option<char> {
+    if let Key::Character(text) = &event.logical_key
+        && let Some(c) = text.chars().next().filter(char::is_ascii_alphabetic)
+    {
+        return Some(c.to_ascii_lowercase());
+    }
+    match event.key_code {
+        KeyCode::KeyA => Some('a'),
+        KeyCode::KeyX => Some('x'),
+        KeyCode::KeyC => Some('c'),
+        KeyCode::KeyV => Some('v'),
+        _ => None,
+    }
+}
+
+/// Lets the platform input method compose text while something is being typed into.
+pub fn update_ime_system(
+    editor_state: Res<EditorState>,
+    ui_state: Res<InspectorUiState>,
+    browsers: Query<(), With<FileBrowser>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let typing = editor_state.focused_input.is_some() || ui_state.is_adding_component || !browsers.is_empty();
+    for mut window in windows.iter_mut() {
+        if window.ime_enabled != typing {
+            window.ime_enabled = typing;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_text_applies_only_insertions_and_backspaces() {
+        let typed = TypedText(vec![
+            TextEdit::Insert("ab".to_string()),
+            TextEdit::Move { motion: CaretMotion::Home, select: false },
+            TextEdit::Backspace,
+            TextEdit::Paste,
+        ]);
+        let mut text = "x".to_string();
+        assert!(typed.apply(&mut text));
+        assert_eq!(text, "xa");
+        assert!(!typed.submitted());
+
+        let mut empty = String::new();
+        assert!(!TypedText(vec![TextEdit::Backspace, TextEdit::Submit]).apply(&mut empty));
+        assert!(TypedText(vec![TextEdit::Submit]).submitted());
+    }
 
-    if keys.just_pressed(KeyCode::Backspace) {
-        ui_state.component_filter.pop();
+    #[test]
+    fn test_next_in_order_wraps_around() {
+        let mut world = World::new();
+        let order: Vec<Entity> = (0..3).map(|_| world.spawn_empty().id()).collect();
+        assert_eq!(next_in_order(&order, order[2], false), Some(order[0]));
+        assert_eq!(next_in_order(&order, order[0], true), Some(order[2]));
+        assert_eq!(next_in_order(&order, Entity::PLACEHOLDER, false), Some(order[0]), "Starts at the first field");
+        assert_eq!(next_in_order(&order, Entity::PLACEHOLDER, true), Some(order[2]));
+        assert_eq!(next_in_order(&[], order[0], false), None);
     }
 
-    for char in &pushed_chars {
-         ui_state.component_filter.push(*char);
+    #[test]
+    fn test_text_keys_leave_modifiers_and_function_keys() {
+        assert!(is_text_key(KeyCode::KeyW) && is_text_key(KeyCode::Space) && is_text_key(KeyCode::Backspace));
+        assert!(!is_text_key(KeyCode::ControlLeft) && !is_text_key(KeyCode::ShiftRight) && !is_text_key(KeyCode::F1));
     }
 }
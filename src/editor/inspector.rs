@@ -4,39 +4,43 @@ use super::styles::*;
 use super::components::*;
 use bevy::ecs::reflect::ReflectComponent;
 use bevy::reflect::ReflectRef;
+use super::prefab::{PrefabLink, PrefabOverride};
+use super::command_palette::fuzzy_filter;
+use super::text_field::{text_field_text, TextField};
 
-type InspectorQueryState<'w, 's> = bevy::ecs::query::QueryState<(Entity, &'static mut PropertyInput, &'static Children)>;
+type InspectorQueryState<'w, 's> = bevy::ecs::query::QueryState<(Entity, &'static PropertyInput, &'static mut TextField)>;
 
 pub fn inspector_ui_system(
     world: &mut World,
-    mut last_state: Local<(Option<Entity>, Option<bevy::ecs::archetype::ArchetypeId>)>,
+    mut last_state: Local<(Option<Entity>, Option<bevy::ecs::archetype::ArchetypeId>, Vec<PrefabOverride>)>,
     mut query_state: Local<Option<InspectorQueryState<'static, 'static>>>,
-    mut text_query_state: Local<Option<bevy::ecs::query::QueryState<&'static mut Text>>>,
 ) {
     let selected = world.resource::<EditorState>().selected_entity;
     let focused = world.resource::<EditorState>().focused_input;
 
     // Initialize query states
     if query_state.is_none() {
-        *query_state = Some(world.query::<(Entity, &mut PropertyInput, &Children)>());
-    }
-    if text_query_state.is_none() {
-        *text_query_state = Some(world.query::<&mut Text>());
+        *query_state = Some(world.query::<(Entity, &PropertyInput, &mut TextField)>());
     }
 
     let Some(entity) = selected else {
         // If nothing selected, clear panel and state
         if last_state.0.is_some() {
              clear_inspector(world);
-             *last_state = (None, None);
+             *last_state = (None, None, Vec::new());
         }
         return;
     };
 
     let current_archetype = world.get_entity(entity).ok().map(|e| e.archetype().id());
+    // Prefab overrides change how fields are drawn. Compare the overrides themselves: reverting one
+    // and adding another in the same frame keeps their count.
+    let overrides = world.get::<PrefabLink>(entity).map(|link| link.overrides.as_slice()).unwrap_or_default();
 
     // Check if we need to rebuild
-    let needs_rebuild = last_state.0 != some(entity) || last_state.1 != current_archetype;
+    let needs_rebuild = last_state.0 != some(entity)
+        || last_state.1 != current_archetype
+        || last_state.2 != overrides;
 
     // Helper to clear
     fn clear_inspector(world: &mut World) {
@@ -61,12 +65,13 @@ pub fn inspector_ui_system(
     fn some<T>(t: T) -> Option<T> { Some(t) }
 
     if needs_rebuild {
+        let overrides = overrides.to_vec();
         clear_inspector(world);
         rebuild_inspector(world, entity);
-        *last_state = (Some(entity), current_archetype);
+        *last_state = (Some(entity), current_archetype, overrides);
     } else {
         // Update values
-         update_values(world, entity, focused, query_state.as_mut().unwrap(), text_query_state.as_mut().unwrap());
+         update_values(world, entity, focused, query_state.as_mut().unwrap());
     }
 }
 
@@ -84,6 +89,7 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
     struct FieldInfo {
         name: String,
         value: String,
+        overridden: bool,
     }
     struct ComponentInfo {
         name: String,
@@ -93,6 +99,8 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
     }
 
     let mut components_to_show: Vec<ComponentInfo> = Vec::new();
+    let prefab_link = world.get::<PrefabLink>(entity).cloned();
+    let override_font = world.get_resource::<AssetServer>().map(override_text_style);
 
     // Use robust component iteration via TypeRegistry and Archetype
     let type_registry = world.resource::<AppTypeRegistry>().clone();
@@ -120,9 +128,13 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
                             let field_name = s.name_at(i).unwrap().to_string();
                             let field_value = s.field_at(i).unwrap();
                             let value_str = format!("{:?}", field_value);
+                            let overridden = prefab_link
+                                .as_ref()
+                                .is_some_and(|link| link.is_overridden(&type_name, &field_name));
                              fields.push(FieldInfo {
                                 name: field_name,
                                 value: value_str,
+                                overridden,
                             });
                         }
                     }
@@ -137,26 +149,6 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
           }
      }
 
-    // IsAdding state
-     let (is_adding, filter) = {
-        let state = world.resource::<InspectorUiState>();
-        (state.is_adding_component, state.component_filter.clone())
-    };
-
-    let mut matching_components = Vec::new();
-    if is_adding {
-        for registration in type_registry.iter() {
-            if let Some(_reflect_component) = registration.data::<ReflectComponent>()
-                 && registration.data::<ReflectDefault>().is_some()
-            {
-                 let name = registration.type_info().type_path_table().short_path();
-                 if name.to_lowercase().contains(&filter.to_lowercase()) {
-                    matching_components.push((name.to_string(), registration.type_id()));
-                 }
-            }
-        }
-    }
-
     world.entity_mut(panel).with_children(|p| {
           // Header
         p.spawn(Node {
@@ -236,9 +228,14 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
                         margin: UiRect::top(Val::Px(2.0)),
                         ..default()
                      }).with_children(|row| {
+                         // Prefab overrides are shown in bold
+                         let label_font = match (&override_font, field.overridden) {
+                             (Some(font), true) => font.clone(),
+                             _ => TextFont { font_size: 12.0, ..default() },
+                         };
                          row.spawn((
                              Text::new(format!("{}: ", field.name)),
-                             TextFont { font_size: 12.0, ..default() },
+                             label_font,
                              TextColor(TEXT_COLOR),
                          ));
 
@@ -254,32 +251,74 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
                                  entity,
                                  component_type_id: info.type_id,
                                  field_name: field.name.clone(),
-                                 current_value: field.value.clone(),
-                             }
-                         )).with_children(|input_container| {
-                             input_container.spawn((
-                                 Text::new(field.value.clone()),
-                                 TextFont { font_size: 12.0, ..default() },
-                                 TextColor(TEXT_COLOR),
-                             ));
-                         });
+                             },
+                             TextField::new(field.value),
+                             children![text_field_text(12.0)],
+                         ));
+
+                         if field.overridden {
+                             row.spawn((
+                                 Button,
+                                 Node {
+                                     padding: UiRect::all(Val::Px(2.0)),
+                                     margin: UiRect::left(Val::Px(2.0)),
+                                     ..default()
+                                 },
+                                 BackgroundColor(BUTTON_COLOR_NORMAL),
+                                 RevertOverrideButton {
+                                     entity,
+                                     component: info.type_name.clone(),
+                                     field: field.name.clone(),
+                                 },
+                             )).with_children(|btn| {
+                                 btn.spawn((
+                                     Text::new("Revert to prefab"),
+                                     TextFont { font_size: 10.0, ..default() },
+                                     TextColor(TEXT_COLOR),
+                                 ));
+                             });
+                         }
                      });
                  }
             });
         }
 
-         // Add Component Section
+         // Add Component Section, filled by component_search_system
         p.spawn((
             Node {
                 margin: UiRect::top(Val::Px(10.0)),
                 flex_direction: FlexDirection::Column,
                 ..default()
             },
-        )).with_children(|section| {
-             if !is_adding {
-                 section.spawn((
+            AddComponentSection,
+        ));
+    });
+}
+
+/// Shows the add component button or, while adding one, the search field and matching components.
+pub fn component_search_system(
+    mut commands: Commands,
+    ui_state: Res<InspectorUiState>,
+    mut editor_state: ResMut<EditorState>,
+    type_registry: Res<AppTypeRegistry>,
+    sections: Query<(Entity, Ref<AddComponentSection>)>,
+    results: Query<Entity, With<ComponentSearchResults>>,
+    mut was_adding: Local<bool>,
+) {
+    let toggled = *was_adding != ui_state.is_adding_component;
+    *was_adding = ui_state.is_adding_component;
+
+    for (section, marker) in sections.iter() {
+        if !toggled && !marker.is_added() {
+            continue;
+        }
+        commands.entity(section).despawn_related::<Children>();
+
+        if !ui_state.is_adding_component {
+            commands.entity(section).with_children(|section| {
+                section.spawn((
                     Button,
-                     Node {
+                    Node {
                         padding: UiRect::all(Val::Px(4.0)),
                         justify_content: JustifyContent::Center,
                         ..default()
@@ -287,57 +326,108 @@ fn rebuild_inspector(world: &mut World, entity: Entity) {
                     BackgroundColor(BUTTON_COLOR_NORMAL),
                     AddComponentButton,
                 )).with_children(|btn| {
-                     btn.spawn((
+                    btn.spawn((
                         Text::new("Add Component"),
                         TextFont { font_size: 14.0, ..default() },
                         TextColor(TEXT_COLOR),
-                     ));
+                    ));
                 });
-             } else {
-                 section.spawn((
-                     Text::new(format!("Search: {}_", filter)),
-                     TextFont { font_size: 14.0, ..default() },
-                     TextColor(TEXT_COLOR),
-                     ComponentSearchInput,
-                 ));
-                 section.spawn((
-                     Button,
-                     Node {
-                         margin: UiRect::top(Val::Px(4.0)),
-                         padding: UiRect::all(Val::Px(4.0)),
-                         ..default()
-                     },
-                     BackgroundColor(BUTTON_COLOR_NORMAL),
-                     AddComponentButton,
-                 )).with_children(|btn| {
-                     btn.spawn((
-                         Text::new("Cancel"),
-                         TextFont { font_size: 12.0, ..default() },
-                         TextColor(TEXT_COLOR),
-                     ));
-                 });
+            });
+            continue;
+        }
 
-                 for (name, _type_id) in matching_components.iter().take(10) {
-                     section.spawn((
-                         Button,
-                         Node {
-                             margin: UiRect::top(Val::Px(2.0)),
-                             padding: UiRect::all(Val::Px(2.0)),
-                             width: Val::Percent(100.0),
-                             ..default()
-                         },
-                         BackgroundColor(BUTTON_COLOR_NORMAL),
-                         ComponentAddButton(name.clone()),
-                     )).with_children(|btn| {
-                         btn.spawn((
-                             Text::new(name),
-                             TextFont { font_size: 12.0, ..default() },
-                             TextColor(TEXT_COLOR),
-                         ));
-                     });
-                 }
-             }
+        let mut search = None;
+        commands.entity(section).with_children(|section| {
+            section.spawn(Node { align_items: AlignItems::Center, ..default() }).with_children(|row| {
+                row.spawn((
+                    Text::new("Search: "),
+                    TextFont { font_size: 14.0, ..default() },
+                    TextColor(TEXT_COLOR),
+                ));
+                search = Some(row.spawn((
+                    Button,
+                    Node {
+                        flex_grow: 1.0,
+                        min_width: Val::Px(50.0),
+                        padding: UiRect::all(Val::Px(2.0)),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    ComponentSearchInput,
+                    TextField::new(ui_state.component_filter.clone()),
+                    children![text_field_text(14.0)],
+                )).id());
+            });
+            section.spawn((
+                Button,
+                Node {
+                    margin: UiRect::top(Val::Px(4.0)),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(BUTTON_COLOR_NORMAL),
+                AddComponentButton,
+            )).with_children(|btn| {
+                btn.spawn((
+                    Text::new("Cancel"),
+                    TextFont { font_size: 12.0, ..default() },
+                    TextColor(TEXT_COLOR),
+                ));
+            });
+            let results = section.spawn((
+                Node { flex_direction: FlexDirection::Column, ..default() },
+                ComponentSearchResults,
+            )).id();
+            spawn_matching_components(section.commands_mut(), results, &type_registry.read(), &ui_state.component_filter);
         });
+        // Type straight into the search
+        editor_state.focused_input = search;
+    }
+
+    if !toggled && ui_state.is_changed() && ui_state.is_adding_component {
+        for results in results.iter() {
+            commands.entity(results).despawn_related::<Children>();
+            spawn_matching_components(&mut commands, results, &type_registry.read(), &ui_state.component_filter);
+        }
+    }
+}
+
+fn spawn_matching_components(
+    commands: &mut Commands,
+    results: Entity,
+    type_registry: &bevy::reflect::TypeRegistry,
+    filter: &str,
+) {
+    // Same matching as the command palette
+    let components = type_registry.iter().filter(|registration| {
+        registration.data::<ReflectComponent>().is_some() && registration.data::<ReflectDefault>().is_some()
+    });
+    let matching_components: Vec<String> =
+        fuzzy_filter(components, |registration| registration.type_info().type_path_table().short_path(), filter)
+            .into_iter()
+            .map(|registration| registration.type_info().type_path_table().short_path().to_string())
+            .collect();
+
+    commands.entity(results).with_children(|section| {
+        for name in matching_components.iter().take(10) {
+            section.spawn((
+                Button,
+                Node {
+                    margin: UiRect::top(Val::Px(2.0)),
+                    padding: UiRect::all(Val::Px(2.0)),
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(BUTTON_COLOR_NORMAL),
+                ComponentAddButton(name.clone()),
+            )).with_children(|btn| {
+                btn.spawn((
+                    Text::new(name),
+                    TextFont { font_size: 12.0, ..default() },
+                    TextColor(TEXT_COLOR),
+                ));
+            });
+        }
     });
 }
 
@@ -346,7 +436,6 @@ fn update_values(
     entity: Entity,
     focused_input: Option<Entity>,
     input_query: &mut InspectorQueryState<'_, '_>,
-    text_query: &mut bevy::ecs::query::QueryState<&mut Text>,
 ) {
      // Gather current values from world
      // We can't iterate PropertyInput and access world.inspect_entity at the same time if we are not careful.
@@ -354,25 +443,22 @@ fn update_values(
 
      // 1. Collect needed updates
      let mut updates = Vec::new();
-     for (input_entity, prop_input, children) in input_query.iter(world) {
+     for (input_entity, prop_input, _) in input_query.iter(world) {
          if Some(input_entity) == focused_input {
              continue; // Don't fight user input
          }
 
-         // We need to fetch the value from the component
-         // We need to fetch the value from the component
-
          // To access component reflectively we need world access.
          // This is tricky inside query iteration loop if we need mutable access later,
          // but here we just need read access to world for reflection, which is blocked by query iteration borrowing world.
          // Solution: Collect identify info, then fetch values, then apply.
-         updates.push((input_entity, prop_input.component_type_id, prop_input.field_name.clone(), children[0]));
+         updates.push((input_entity, prop_input.component_type_id, prop_input.field_name.clone()));
      }
 
      let type_registry_arc = world.resource::<AppTypeRegistry>().clone();
      let type_registry = type_registry_arc.read();
 
-     for (input_entity, type_id, field_name, text_child) in updates {
+     for (input_entity, type_id, field_name) in updates {
           let mut new_value_str = None;
 
            if let Some(registration) = type_registry.get(type_id)
@@ -384,18 +470,43 @@ fn update_values(
                new_value_str = Some(format!("{:?}", field));
            }
 
-          if let Some(val) = new_value_str {
-               // Update PropertyInput component state
-               if let Ok((_, mut prop_input, _)) = input_query.get_mut(world, input_entity)
-                   && prop_input.current_value != val
-               {
-                   prop_input.current_value = val.clone();
-
-                   // Update Text
-                   if let Ok(mut text) = text_query.get_mut(world, text_child) {
-                       **text = val;
-                   }
-               }
+          if let Some(val) = new_value_str
+              && let Ok((_, _, mut text_field)) = input_query.get_mut(world, input_entity)
+              && text_field.text != val
+          {
+              text_field.set_text(val);
           }
      }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swapping_overrides_redraws_their_markers() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<EditorState>();
+        app.register_type::<Transform>();
+        app.add_systems(Update, inspector_ui_system);
+        app.world_mut().spawn(InspectorPanel);
+
+        let override_of = |field: &str| PrefabOverride { component: Transform::type_path().to_string(), field: field.to_string() };
+        let link = PrefabLink { overrides: vec![override_of("translation")], ..default() };
+        let entity = app.world_mut().spawn((Transform::default(), link)).id();
+        app.world_mut().resource_mut::<EditorState>().selected_entity = Some(entity);
+        let revert_buttons = |app: &mut App| {
+            let world = app.world_mut();
+            world.query::<&RevertOverrideButton>().iter(world).map(|button| button.field.clone()).collect::<Vec<_>>()
+        };
+
+        app.update();
+        assert_eq!(revert_buttons(&mut app), ["translation"]);
+
+        // Same number of overrides, different fields
+        app.world_mut().get_mut::<PrefabLink>(entity).unwrap().overrides = vec![override_of("scale")];
+        app.update();
+        assert_eq!(revert_buttons(&mut app), ["scale"]);
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use super::styles::*;
+
+/// Default location of the keymap file, relative to the working directory.
+pub const KEYMAP_FILE: &str = "editor_keymap.ron";
+
+/// Something the editor does on a key press.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EditorAction {
+    ToggleEditor,
+    CommandPalette,
+    CameraForward,
+    CameraBack,
+    CameraLeft,
+    CameraRight,
+    CameraUp,
+    CameraDown,
+    /// Held to fly faster.
+    CameraBoost,
+    /// Held with the left mouse button to orbit around the camera's pivot.
+    CameraOrbit,
+    /// Moves the camera to show the selected entity.
+    FrameSelection,
+    TextSubmit,
+    TextCancel,
+    TextBackspace,
+    TextDelete,
+    /// Shift reverses the direction.
+    TextNextField,
+}
+
+impl EditorAction {
+    pub const ALL: [EditorAction; 16] = [
+        EditorAction::ToggleEditor,
+        EditorAction::CommandPalette,
+        EditorAction::CameraForward,
+        EditorAction::CameraBack,
+        EditorAction::CameraLeft,
+        EditorAction::CameraRight,
+        EditorAction::CameraUp,
+        EditorAction::CameraDown,
+        EditorAction::CameraBoost,
+        EditorAction::CameraOrbit,
+        EditorAction::FrameSelection,
+        EditorAction::TextSubmit,
+        EditorAction::TextCancel,
+        EditorAction::TextBackspace,
+        EditorAction::TextDelete,
+        EditorAction::TextNextField,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorAction::ToggleEditor => "Toggle editor",
+            EditorAction::CommandPalette => "Command palette",
+            EditorAction::CameraForward => "Camera forward",
+            EditorAction::CameraBack => "Camera back",
+            EditorAction::CameraLeft => "Camera left",
+            EditorAction::CameraRight => "Camera right",
+            EditorAction::CameraUp => "Camera up",
+            EditorAction::CameraDown => "Camera down",
+            EditorAction::CameraBoost => "Camera fast (hold)",
+            EditorAction::CameraOrbit => "Orbit with left mouse (hold)",
+            EditorAction::FrameSelection => "Frame selection",
+            EditorAction::TextSubmit => "Confirm field",
+            EditorAction::TextCancel => "Cancel field edit",
+            EditorAction::TextBackspace => "Delete before caret",
+            EditorAction::TextDelete => "Delete after caret",
+            EditorAction::TextNextField => "Next field (Shift: previous)",
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// A key with the modifiers that have to be held. `ctrl` also accepts Cmd (Super) for macOS.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub ctrl: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub shift: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub const fn new(key: KeyCode) -> Self {
+        Self { key, ctrl: false, shift: false, alt: false }
+    }
+
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub const fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Which of Ctrl, Shift and Alt are held.
+    fn held_modifiers(keys: &ButtonInput<KeyCode>) -> (bool, bool, bool) {
+        (
+            keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]),
+            keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        )
+    }
+
+    /// Whether `key` completes this chord. Other modifiers may be held too, e.g. Shift to select
+    /// while moving the caret.
+    pub fn matches(&self, key: KeyCode, keys: &ButtonInput<KeyCode>) -> bool {
+        let (ctrl, shift, alt) = Self::held_modifiers(keys);
+        key == self.key && (ctrl || !self.ctrl) && (shift || !self.shift) && (alt || !self.alt)
+    }
+
+    /// Whether the chord is held, allowing extra modifiers.
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.pressed(self.key) && self.matches(self.key, keys)
+    }
+
+    /// Whether the chord was pressed this frame with exactly its modifiers, so Ctrl+S doesn't
+    /// also fire Ctrl+Shift+S.
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key) && Self::held_modifiers(keys) == (self.ctrl, self.shift, self.alt)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("Key").or_else(|| key.strip_prefix("Digit")).unwrap_or(&key);
+        write!(f, "{}", key)
+    }
+}
+
+/// Key chords bound to each [`EditorAction`]. Read from a RON file like
+/// `(bindings: { CameraForward: [(key: KeyZ)], ToggleEditor: [(key: F2, ctrl: true)] })`;
+/// actions missing from the file keep their default bindings.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct EditorKeymap {
+    pub bindings: BTreeMap<EditorAction, Vec<KeyChord>>,
+}
+
+impl Default for EditorKeymap {
+    fn default() -> Self {
+        use EditorAction::*;
+        let bindings = [
+            (ToggleEditor, vec![KeyChord::new(KeyCode::F1)]),
+            (CommandPalette, vec![KeyChord::new(KeyCode::KeyP).ctrl().shift()]),
+            (CameraForward, vec![KeyChord::new(KeyCode::KeyW)]),
+            (CameraBack, vec![KeyChord::new(KeyCode::KeyS)]),
+            (CameraLeft, vec![KeyChord::new(KeyCode::KeyA)]),
+            (CameraRight, vec![KeyChord::new(KeyCode::KeyD)]),
+            (CameraUp, vec![KeyChord::new(KeyCode::KeyE)]),
+            (CameraDown, vec![KeyChord::new(KeyCode::KeyQ)]),
+            (CameraBoost, vec![KeyChord::new(KeyCode::ShiftLeft), KeyChord::new(KeyCode::ShiftRight)]),
+            (CameraOrbit, vec![KeyChord::new(KeyCode::AltLeft), KeyChord::new(KeyCode::AltRight)]),
+            (FrameSelection, vec![KeyChord::new(KeyCode::KeyF)]),
+            (TextSubmit, vec![KeyChord::new(KeyCode::Enter), KeyChord::new(KeyCode::NumpadEnter)]),
+            (TextCancel, vec![KeyChord::new(KeyCode::Escape)]),
+            (TextBackspace, vec![KeyChord::new(KeyCode::Backspace)]),
+            (TextDelete, vec![KeyChord::new(KeyCode::Delete)]),
+            (TextNextField, vec![KeyChord::new(KeyCode::Tab)]),
+        ];
+        Self { bindings: bindings.into_iter().collect() }
+    }
+}
+
+impl EditorKeymap {
+    /// Reads a keymap file over the default bindings. A missing or invalid file gives the defaults.
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match ron::from_str::<Self>(&text) {
+            Ok(file) => {
+                let mut keymap = Self::default();
+                keymap.bindings.extend(file.bindings);
+                keymap
+            }
+            Err(e) => {
+                warn!("Ignoring invalid keymap file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn chords(&self, action: EditorAction) -> &[KeyChord] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn pressed(&self, action: EditorAction, keys: &ButtonInput<KeyCode>) -> bool {
+        self.chords(action).iter().any(|chord| chord.pressed(keys))
+    }
+
+    pub fn just_pressed(&self, action: EditorAction, keys: &ButtonInput<KeyCode>) -> bool {
+        self.chords(action).iter().any(|chord| chord.just_pressed(keys))
+    }
+
+    /// The first of `actions` that `key` triggers, for keys read from keyboard messages.
+    pub fn action_for(&self, actions: &[EditorAction], key: KeyCode, keys: &ButtonInput<KeyCode>) -> Option<EditorAction> {
+        actions
+            .iter()
+            .copied()
+            .find(|&action| self.chords(action).iter().any(|chord| chord.matches(key, keys)))
+    }
+
+    /// The bindings of `action` as shown to the user, e.g. `Enter, NumpadEnter`.
+    pub fn describe(&self, action: EditorAction) -> String {
+        let chords: Vec<String> = self.chords(action).iter().map(ToString::to_string).collect();
+        if chords.is_empty() { "Unbound".to_string() } else { chords.join(", ") }
+    }
+}
+
+#[derive(Component)]
+pub struct ShortcutsButton;
+
+/// Read-only list of the [`EditorKeymap`], opened from the menu bar.
+#[derive(Component)]
+pub struct ShortcutsPanel;
+
+pub fn toggle_shortcuts_panel(
+    interactions: Query<&Interaction, (Changed<Interaction>, With<ShortcutsButton>)>,
+    mut panels: Query<&mut Visibility, With<ShortcutsPanel>>,
+) {
+    for interaction in interactions.iter() {
+        if *interaction == Interaction::Pressed {
+            for mut vis in panels.iter_mut() {
+                *vis = match *vis {
+                    Visibility::Hidden => Visibility::Inherited,
+                    _ => Visibility::Hidden,
+                };
+            }
+        }
+    }
+}
+
+/// Lists the bindings in the [`ShortcutsPanel`] whenever the keymap changes.
+pub fn update_shortcuts_panel(
+    mut commands: Commands,
+    keymap: Res<EditorKeymap>,
+    panels: Query<(Entity, Ref<ShortcutsPanel>)>,
+) {
+    for (panel, marker) in panels.iter() {
+        if !keymap.is_changed() && !marker.is_added() {
+            continue;
+        }
+        commands.entity(panel).despawn_related::<Children>();
+        commands.entity(panel).with_children(|panel| {
+            panel.spawn((
+                Text::new("Keyboard Shortcuts"),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(TEXT_COLOR),
+            ));
+            for action in EditorAction::ALL {
+                panel.spawn(Node {
+                    justify_content: JustifyContent::SpaceBetween,
+                    column_gap: Val::Px(12.0),
+                    ..default()
+                }).with_children(|row| {
+                    row.spawn((
+                        Text::new(action.label()),
+                        TextFont { font_size: 12.0, ..default() },
+                        TextColor(TEXT_COLOR),
+                    ));
+                    row.spawn((
+                        Text::new(keymap.describe(action)),
+                        TextFont { font_size: 12.0, ..default() },
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chords_need_exactly_their_modifiers_when_pressed() {
+        let mut keymap = EditorKeymap::default();
+        keymap.bindings.insert(EditorAction::CameraForward, vec![KeyChord::new(KeyCode::KeyZ)]);
+        keymap.bindings.insert(EditorAction::TextSubmit, vec![KeyChord::new(KeyCode::Enter).ctrl()]);
+        assert_eq!(keymap.describe(EditorAction::TextSubmit), "Ctrl+Enter");
+        assert_eq!(keymap.describe(EditorAction::TextNextField), "Tab");
+
+        // Held chords allow extra modifiers; presses need exactly theirs
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::ShiftLeft);
+        keys.press(KeyCode::KeyZ);
+        keys.press(KeyCode::F1);
+        assert!(keymap.pressed(EditorAction::CameraForward, &keys));
+        assert!(!keymap.pressed(EditorAction::CameraBack, &keys));
+        assert!(!keymap.just_pressed(EditorAction::ToggleEditor, &keys));
+        keys.release(KeyCode::ShiftLeft);
+        assert!(keymap.just_pressed(EditorAction::ToggleEditor, &keys));
+    }
+}
@@ -86,15 +86,14 @@ pub fn transfer_logs_system(
     mut editor_logs: ResMut<EditorLogs>,
     capture_layer: Option<Res<SharedLogBufferResource>>, // We'll put the shared buffer in a resource too
 ) {
-    if let Some(shared) = capture_layer {
-        if let Ok(mut buffer) = shared.0.buffer.lock() {
-            if !buffer.is_empty() {
-                editor_logs.messages.extend(buffer.drain(..));
-                // Optional: Limit log history
-                while editor_logs.messages.len() > 1000 {
-                    editor_logs.messages.pop_front();
-                }
-            }
+    if let Some(shared) = capture_layer
+        && let Ok(mut buffer) = shared.0.buffer.lock()
+        && !buffer.is_empty()
+    {
+        editor_logs.messages.extend(buffer.drain(..));
+        // Optional: Limit log history
+        while editor_logs.messages.len() > 1000 {
+            editor_logs.messages.pop_front();
         }
     }
 }
@@ -1,11 +1,23 @@
 #![allow(clippy::type_complexity)]
+use crate::editor::autosave::AutosaveState;
 use crate::editor::components::*;
+use crate::editor::dialog::{spawn_dialog, DialogChoice, ModalDialog};
+use crate::editor::file_dialog::{FileDialogKind, FileDialogRequest, FileDialogResult, FilePurpose, OpenFileDialog};
+use crate::editor::resources::{EditorConfig, EditorState};
+use crate::editor::preferences::EditorPreferences;
+use crate::editor::prefab::{InstantiatePrefabCommand, SavePrefabCommand};
+use crate::editor::migration::SceneMigrations;
+use crate::editor::save_filter::{SaveFilter, SceneEntityFilter};
+use crate::editor::scene_asset::SceneWatch;
+use crate::editor::scene_format;
+use crate::editor::templates::{discover_templates, NewSceneCommand, SceneTemplate, TEMPLATES_DIR};
 use crate::editor::styles::*;
 use bevy::prelude::*;
 use std::fs::File;
 use std::io::Write;
-use bevy::ecs::relationship::Relationship;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use bevy::tasks::{block_on, poll_once, IoTaskPool, Task};
 
 #[derive(Resource, Default)]
 pub struct SceneInfo {
@@ -47,60 +59,225 @@ pub fn menu_action_system(
         (Changed<Interaction>, With<MenuButtonAction>),
     >,
     mut commands: Commands,
-    mut dropdown_query: Query<&mut Visibility, With<FileMenuDropdown>>,
-    mut scene_info: ResMut<SceneInfo>,
+    mut dropdown_query: Query<&mut Visibility, (With<FileMenuDropdown>, Without<RecentScenesMenu>)>,
+    mut recent_menu_query: Query<&mut Visibility, With<RecentScenesMenu>>,
+    scene_info: Res<SceneInfo>,
+    editor_state: Res<EditorState>,
+    dialogs: Query<(), With<ModalDialog>>,
 ) {
     for (interaction, mut color, menu_action) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *color = BUTTON_COLOR_PRESSED.into();
+                if let MenuAction::RecentScenes = menu_action.action {
+                    for mut vis in recent_menu_query.iter_mut() {
+                        *vis = match *vis {
+                            Visibility::Hidden => Visibility::Inherited,
+                            _ => Visibility::Hidden,
+                        };
+                    }
+                    continue;
+                }
                 // Hide dropdown
                 for mut vis in dropdown_query.iter_mut() {
                     *vis = Visibility::Hidden;
                 }
+                for mut vis in recent_menu_query.iter_mut() {
+                    *vis = Visibility::Hidden;
+                }
 
-                match menu_action.action {
-                    MenuAction::Exit => {
-                        std::process::exit(0);
-                    }
-                    MenuAction::Save => {
-                         if scene_info.file_path.is_some() {
-                             commands.insert_resource(SaveRequest);
-                         } else {
-                             // Treat as Save As
-                             let dir = std::env::current_dir().unwrap_or_default();
-                             if let Some(path) = rfd::FileDialog::new()
-                                 .set_directory(&dir)
-                                 .add_filter("Scene", &["scn.ron"])
-                                 .save_file()
-                             {
-                                 scene_info.file_path = Some(path);
-                                 commands.insert_resource(SaveRequest);
-                             }
-                         }
-                    }
-                    MenuAction::SaveAs => {
-                        let dir = std::env::current_dir().unwrap_or_default();
-                        if let Some(path) = rfd::FileDialog::new()
-                            .set_directory(&dir)
-                            .add_filter("Scene", &["scn.ron"])
-                            .save_file()
-                        {
-                             scene_info.file_path = Some(path);
-                             commands.insert_resource(SaveRequest);
-                         }
-                    }
-                    MenuAction::Load => {
-                        let dir = std::env::current_dir().unwrap_or_default();
-                        if let Some(path) = rfd::FileDialog::new()
-                            .set_directory(&dir)
-                            .add_filter("Scene", &["scn.ron"])
-                            .pick_file()
-                        {
-                             scene_info.file_path = Some(path);
-                             commands.insert_resource(LoadRequest);
-                        }
+                run_menu_action(
+                    &mut commands,
+                    menu_action.action,
+                    &scene_info,
+                    editor_state.selected_entity,
+                    !dialogs.is_empty(),
+                );
+            }
+            Interaction::Hovered => {
+                *color = BUTTON_COLOR_HOVER.into();
+            }
+            Interaction::None => {
+                *color = BUTTON_COLOR_NORMAL.into();
+            }
+        }
+    }
+}
+
+/// Runs a File menu action, from the menu or the command palette.
+pub fn run_menu_action(
+    commands: &mut Commands,
+    action: MenuAction,
+    scene_info: &SceneInfo,
+    selected: Option<Entity>,
+    dialog_open: bool,
+) {
+    match action {
+        MenuAction::NewScene => {
+            if dialog_open {
+                return;
+            }
+            let mut buttons: Vec<(String, DialogChoice)> = discover_templates(Path::new(TEMPLATES_DIR))
+                .into_iter()
+                .map(|template| (template.label(), DialogChoice::NewScene(template)))
+                .collect();
+            buttons.push(("Cancel".to_string(), DialogChoice::Cancel));
+            spawn_dialog(commands, "Start a new scene from:", buttons);
+        }
+        MenuAction::Exit => {
+            guard_unsaved_changes(commands, scene_info, dialog_open, GuardedAction::Exit);
+        }
+        MenuAction::Save => {
+            if scene_info.file_path.is_some() {
+                commands.insert_resource(SaveRequest);
+            } else {
+                // Treat as Save As
+                commands.queue(OpenFileDialog(FileDialogRequest::new(
+                    FileDialogKind::Save,
+                    FilePurpose::SaveScene { then: None },
+                )));
+            }
+        }
+        MenuAction::SaveAs => {
+            commands.queue(OpenFileDialog(FileDialogRequest::new(
+                FileDialogKind::Save,
+                FilePurpose::SaveScene { then: None },
+            )));
+        }
+        MenuAction::Load => {
+            commands.queue(OpenFileDialog(FileDialogRequest::new(FileDialogKind::Open, FilePurpose::LoadScene)));
+        }
+        MenuAction::RecentScenes => {}
+        MenuAction::Import | MenuAction::ImportUnderParent => {
+            let under_parent = action == MenuAction::ImportUnderParent;
+            commands.queue(OpenFileDialog(FileDialogRequest::new(
+                FileDialogKind::Open,
+                FilePurpose::ImportScene { under_parent },
+            )));
+        }
+        MenuAction::SavePrefab => {
+            let Some(root) = selected else {
+                warn!("Select an entity to save as a prefab");
+                return;
+            };
+            commands.queue(OpenFileDialog(FileDialogRequest::new(
+                FileDialogKind::Save,
+                FilePurpose::SavePrefab(root),
+            )));
+        }
+        MenuAction::InstantiatePrefab => {
+            commands.queue(OpenFileDialog(FileDialogRequest::new(
+                FileDialogKind::Open,
+                FilePurpose::InstantiatePrefab,
+            )));
+        }
+    }
+}
+
+/// Runs a File menu action from a world, e.g. in a command palette entry.
+pub struct RunMenuAction(pub MenuAction);
+
+impl bevy::ecs::system::Command for RunMenuAction {
+    fn apply(self, world: &mut World) {
+        let mut dialogs = world.query_filtered::<(), With<ModalDialog>>();
+        let dialog_open = dialogs.iter(world).next().is_some();
+        let mut queue = bevy::ecs::world::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        run_menu_action(
+            &mut commands,
+            self.0,
+            world.resource::<SceneInfo>(),
+            world.resource::<EditorState>().selected_entity,
+            dialog_open,
+        );
+        queue.apply(world);
+    }
+}
+
+/// Fills the Recent Scenes submenu when it opens or the list changes.
+pub fn update_recent_scenes_menu(
+    mut commands: Commands,
+    menu_query: Query<(Entity, &Visibility), With<RecentScenesMenu>>,
+    opened: Query<(), (With<RecentScenesMenu>, Changed<Visibility>)>,
+    mut preferences: ResMut<EditorPreferences>,
+) {
+    if opened.is_empty() && !preferences.is_changed() {
+        return;
+    }
+    for (menu, visibility) in menu_query.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        if preferences.recent_scenes.iter().any(|p| !p.is_file()) {
+            preferences.prune_missing();
+        }
+
+        commands.entity(menu).despawn_related::<Children>().with_children(|menu| {
+            if preferences.recent_scenes.is_empty() {
+                menu.spawn((
+                    Node { padding: UiRect::all(Val::Px(5.0)), ..default() },
+                )).with_children(|row| {
+                    row.spawn((
+                        Text::new("No recent scenes"),
+                        TextFont::default(),
+                        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    ));
+                });
+            }
+            for path in &preferences.recent_scenes {
+                let label = path.file_name().map_or_else(|| path.to_string_lossy(), |n| n.to_string_lossy());
+                menu.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    RecentSceneButton(path.clone()),
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new(label.into_owned()),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+            }
+            menu.spawn((
+                Button,
+                menu_button_style(),
+                BackgroundColor(BUTTON_COLOR_NORMAL),
+                ClearRecentButton,
+            )).with_children(|btn| {
+                btn.spawn((
+                    Text::new("Clear recent"),
+                    TextFont::default(),
+                    TextColor(TEXT_COLOR),
+                ));
+            });
+        });
+    }
+}
+
+pub fn recent_scenes_menu_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, Option<&RecentSceneButton>),
+        (Changed<Interaction>, Or<(With<RecentSceneButton>, With<ClearRecentButton>)>),
+    >,
+    mut commands: Commands,
+    mut menu_query: Query<&mut Visibility, Or<(With<FileMenuDropdown>, With<RecentScenesMenu>)>>,
+    mut preferences: ResMut<EditorPreferences>,
+    scene_info: Res<SceneInfo>,
+    dialogs: Query<(), With<ModalDialog>>,
+) {
+    for (interaction, mut color, recent) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BUTTON_COLOR_PRESSED.into();
+                for mut vis in menu_query.iter_mut() {
+                    *vis = Visibility::Hidden;
+                }
+                match recent {
+                    Some(RecentSceneButton(path)) => {
+                        guard_unsaved_changes(&mut commands, &scene_info, !dialogs.is_empty(), GuardedAction::Load(path.clone()));
                     }
+                    None => preferences.recent_scenes.clear(),
                 }
             }
             Interaction::Hovered => {
@@ -113,14 +290,170 @@ pub fn menu_action_system(
     }
 }
 
+/// Carries out the menu action a file dialog was opened for.
+pub fn handle_file_dialog_results(
+    mut results: MessageReader<FileDialogResult>,
+    mut commands: Commands,
+    mut scene_info: ResMut<SceneInfo>,
+    config: Res<EditorConfig>,
+    dialogs: Query<(), With<ModalDialog>>,
+) {
+    for result in results.read() {
+        let Some(path) = result.path.clone() else {
+            continue;
+        };
+        match &result.purpose {
+            FilePurpose::SaveScene { then } => {
+                if scene_format::SceneEncoding::for_path(&path) == scene_format::SceneEncoding::Binary {
+                    warn!(
+                        "Saving {:?} as a binary scene; future field migrations can't upgrade it, so keep a RON copy",
+                        path
+                    );
+                }
+                scene_info.file_path = Some(path);
+                commands.insert_resource(SaveRequest);
+                if let Some(action) = then {
+                    commands.insert_resource(PendingAction { action: action.clone(), discard_changes: false });
+                }
+            }
+            FilePurpose::LoadScene => {
+                guard_unsaved_changes(&mut commands, &scene_info, !dialogs.is_empty(), GuardedAction::Load(path));
+            }
+            FilePurpose::ImportScene { under_parent } => {
+                commands.insert_resource(ImportRequest {
+                    path,
+                    under_parent: *under_parent || config.import_under_parent,
+                });
+            }
+            FilePurpose::SavePrefab(root) => {
+                commands.queue(SavePrefabCommand { root: *root, path });
+            }
+            FilePurpose::InstantiatePrefab => {
+                commands.queue(InstantiatePrefabCommand { path, parent: None });
+            }
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct SaveRequest;
 
+/// An action that throws away the current scene, so it has to be confirmed while the scene is dirty.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuardedAction {
+    Exit,
+    Load(PathBuf),
+    NewScene(SceneTemplate),
+}
+
+/// A [`GuardedAction`] waiting for the save that was requested before it.
+#[derive(Resource, Debug)]
+pub struct PendingAction {
+    pub action: GuardedAction,
+    /// Run even if the scene is still dirty, because the user chose to discard it.
+    pub discard_changes: bool,
+}
+
+/// Runs `action` right away if the scene has no unsaved changes, otherwise asks whether to save
+/// or discard them first.
+pub fn guard_unsaved_changes(
+    commands: &mut Commands,
+    scene_info: &SceneInfo,
+    dialog_open: bool,
+    action: GuardedAction,
+) {
+    if !scene_info.is_dirty {
+        commands.insert_resource(PendingAction { action, discard_changes: false });
+        return;
+    }
+    if dialog_open {
+        return;
+    }
+
+    let name = scene_info
+        .file_path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map_or("the untitled scene".to_string(), |name| name.to_string_lossy().into_owned());
+    spawn_dialog(
+        commands,
+        &format!("Save changes to {}?", name),
+        vec![
+            ("Save".to_string(), DialogChoice::SaveThen(action.clone())),
+            ("Discard".to_string(), DialogChoice::DiscardThen(action)),
+            ("Cancel".to_string(), DialogChoice::Cancel),
+        ],
+    );
+}
+
+/// Runs the [`PendingAction`] once the save before it has gone through.
+pub fn run_pending_action(
+    mut commands: Commands,
+    pending: Option<Res<PendingAction>>,
+    mut scene_info: ResMut<SceneInfo>,
+    save_tasks: Option<Res<SaveTasks>>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    // Wait until the file is on disk; a failed save marks the scene dirty again
+    if save_tasks.is_some_and(|tasks| !tasks.0.is_empty()) {
+        return;
+    }
+    commands.remove_resource::<PendingAction>();
+    if scene_info.is_dirty && !pending.discard_changes {
+        warn!("Scene was not saved; {:?} cancelled", pending.action);
+        return;
+    }
+
+    match &pending.action {
+        GuardedAction::Exit => {
+            app_exit.write(AppExit::Success);
+        }
+        GuardedAction::Load(path) => {
+            scene_info.file_path = Some(path.clone());
+            commands.insert_resource(LoadRequest::default());
+        }
+        GuardedAction::NewScene(template) => {
+            commands.queue(NewSceneCommand { template: template.clone() });
+        }
+    }
+}
+
+/// Routes window close buttons through [`guard_unsaved_changes`].
+///
+/// Bevy closes windows on its own unless `WindowPlugin::close_when_requested` is `false`;
+/// [`EditorPlugin`](super::EditorPlugin) logs an error if it isn't.
+pub fn handle_window_close_requested(
+    mut commands: Commands,
+    mut close_requested: MessageReader<bevy::window::WindowCloseRequested>,
+    scene_info: Res<SceneInfo>,
+    dialogs: Query<(), With<ModalDialog>>,
+) {
+    if close_requested.read().count() > 0 {
+        guard_unsaved_changes(&mut commands, &scene_info, !dialogs.is_empty(), GuardedAction::Exit);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LoadRequest {
+    /// Read the scene from this file instead of [`SceneInfo::file_path`], keeping the scene's path.
+    /// Used to restore a recovery file; the restored scene counts as unsaved.
+    pub source: Option<PathBuf>,
+}
+
+/// Spawns a scene file into the current world without clearing it.
 #[derive(Resource)]
-pub struct LoadRequest;
+pub struct ImportRequest {
+    pub path: PathBuf,
+    /// Spawn the imported root entities under a new entity named after the file.
+    pub under_parent: bool,
+}
 
+/// The encoded contents of the last save, in the format its file extension asked for.
 #[derive(Resource, Default)]
-pub struct LastSavedScene(pub String);
+pub struct LastSavedScene(pub Vec<u8>);
 
 pub fn save_system(
     world: &mut World,
@@ -129,160 +462,300 @@ pub fn save_system(
         return;
     }
 
-    let mut entities_to_save = Vec::new();
+    let encoding = world
+        .get_resource::<SceneInfo>()
+        .and_then(|info| info.file_path.as_deref())
+        .map(scene_format::SceneEncoding::for_path)
+        .unwrap_or_default();
+    let Some(serialized_scene) = encode_world_scene(world, encoding) else {
+        return;
+    };
 
-    let mut query = world.query::<Entity>();
-    let all_entities: Vec<Entity> = query.iter(world).collect();
+    world.insert_resource(LastSavedScene(serialized_scene));
 
-    for entity in all_entities {
-        let entity_ref = world.entity(entity);
+    // Reset dirty flag
+    if let Some(mut info) = world.get_resource_mut::<SceneInfo>() {
+        info.is_dirty = false;
+    }
+}
 
-        // Exclude specific components
-        if entity_ref.contains::<EditorRoot>() ||
-           entity_ref.contains::<crate::editor::camera::EditorCamera>() ||
-           entity_ref.contains::<bevy::window::PrimaryWindow>() {
-            continue;
-        }
+/// Serializes every scene entity in `world` the way a save writes it.
+pub fn serialize_world_scene(world: &mut World) -> Option<String> {
+    encode_world_scene(world, scene_format::SceneEncoding::Ron).and_then(|bytes| String::from_utf8(bytes).ok())
+}
 
-        // Check lineage for EditorRoot
-        let mut is_editor_child = false;
-        let mut current = entity;
+/// [`serialize_world_scene`] in the given encoding.
+pub fn encode_world_scene(world: &mut World, encoding: scene_format::SceneEncoding) -> Option<Vec<u8>> {
+    // Editor entities are tagged `EditorOnly` (including descendants) when spawned
+    let mut query = world.query_filtered::<(Entity, Option<&scene_format::SceneId>), SceneEntityFilter>();
+    let entities_to_save: Vec<(Entity, Option<scene_format::SceneId>)> =
+        query.iter(world).map(|(entity, id)| (entity, id.copied())).collect();
 
-        while let Some(parent) = world.get::<ChildOf>(current) {
-            current = parent.get();
-            if world.get::<EditorRoot>(current).is_some() {
-                is_editor_child = true;
-                break;
-            }
-        }
+    use bevy::scene::DynamicSceneBuilder;
 
-        if is_editor_child {
-            continue;
+    // Give every saved entity a stable id so keys don't change between saves. New entities keep
+    // theirs, so it doesn't shift when other entities are added or removed before the next save.
+    let ids = scene_format::scene_ids(entities_to_save.iter().copied());
+    for (&entity, &id) in &ids {
+        if world.get::<scene_format::SceneId>(entity) != Some(&id) {
+            world.entity_mut(entity).insert(id);
         }
-
-        entities_to_save.push(entity);
     }
 
-    use bevy::scene::DynamicSceneBuilder;
+    // Leave out runtime-derived components (deny-list and `#[reflect(SkipSave)]` types)
+    let component_filter = {
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        world
+            .get_resource::<SaveFilter>()
+            .cloned()
+            .unwrap_or_default()
+            .scene_filter(&type_registry)
+    };
 
     // Correctly chain the builder
     let mut scene = DynamicSceneBuilder::from_world(world)
-        .extract_entities(entities_to_save.into_iter())
+        .with_component_filter(component_filter)
+        .extract_entities(entities_to_save.into_iter().map(|(entity, _)| entity))
         .build();
-
-    // Filter out problem components directly from the scene data
-    for entity in &mut scene.entities {
-        entity.components.retain(|component| {
-            let name = component.reflect_type_path();
-            if name.contains("VisibilityClass") ||
-               name.contains("Mesh3d") ||
-               name.contains("MeshMaterial3d") {
-                info!("Removing component from scene: {}", name);
-                return false;
-            }
-            true
-        });
+    scene_format::set_scene_ids(&mut scene, &ids);
+    if let Some(path) = world.get_resource::<SceneInfo>().and_then(|info| info.file_path.as_deref()) {
+        crate::editor::prefab::relativize_prefab_links(&mut scene, path);
     }
 
+    let options = scene_format::SceneSaveOptions {
+        version: scene_format::SCENE_FORMAT_VERSION,
+        migrations: world
+            .get_resource::<SceneMigrations>()
+            .map(SceneMigrations::plugin_versions)
+            .unwrap_or_default(),
+        omit_default_fields: world
+            .get_resource::<EditorConfig>()
+            .is_some_and(|config| config.omit_default_fields),
+    };
+
     // Serialize with the default registry
-    let serialized_scene = {
-        let type_registry = world.resource::<AppTypeRegistry>();
-        let type_registry = type_registry.read();
+    let type_registry = world.resource::<AppTypeRegistry>();
+    let type_registry = type_registry.read();
 
-        match scene.serialize(&type_registry) {
-            Ok(s) => s,
-            Err(e) => {
-                error!("Failed to serialize scene: {}", e);
-                return;
-            }
+    match scene_format::serialize_scene_as(&scene, &type_registry, &options, encoding) {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            error!("Failed to serialize scene: {}", e);
+            None
         }
-    };
+    }
+}
 
-    world.insert_resource(LastSavedScene(serialized_scene));
+/// Scene writes running on the IO task pool.
+#[derive(Resource, Default)]
+pub struct SaveTasks(pub Vec<(PathBuf, Task<std::io::Result<()>>)>);
 
-    // Reset dirty flag
-    if let Some(mut info) = world.get_resource_mut::<SceneInfo>() {
-        info.is_dirty = false;
-    }
+/// Result of the last save, shown in the menu bar.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct SaveStatus {
+    pub message: String,
+    pub is_error: bool,
 }
 
+/// Hands the serialized scene to the IO task pool; [`poll_save_tasks`] reports the outcome.
 pub fn save_to_file_system(
     saved_scene: Res<LastSavedScene>,
-    scene_info: Res<SceneInfo>,
+    mut scene_info: ResMut<SceneInfo>,
+    mut tasks: ResMut<SaveTasks>,
 ) {
-    if saved_scene.is_changed() {
-        if saved_scene.0.is_empty() {
-            return;
+    if !saved_scene.is_changed() || saved_scene.0.is_empty() {
+        return;
+    }
+
+    let Some(path) = scene_info.file_path.clone() else {
+        error!("Cannot save: No file path set in SceneInfo");
+        scene_info.is_dirty = true;
+        return;
+    };
+    let contents = saved_scene.0.clone();
+    let target = path.clone();
+    let task = IoTaskPool::get().spawn(async move { write_atomic(&target, &contents) });
+    tasks.0.push((path, task));
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over `path`, so a failed
+/// write never leaves a truncated file behind.
+///
+/// Every call gets its own temporary file, so concurrent writes to the same path (e.g. a save and
+/// an autosave) don't clobber each other's halves; the last rename wins.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.{}.tmp", std::process::id(), NEXT_TEMP.fetch_add(1, Ordering::Relaxed)));
+    let temp = path.with_file_name(temp_name);
+
+    let result = File::create(&temp)
+        .and_then(|mut file| file.write_all(contents).and_then(|()| file.sync_all()))
+        .and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+pub fn poll_save_tasks(
+    mut tasks: ResMut<SaveTasks>,
+    mut scene_info: ResMut<SceneInfo>,
+    mut autosave: Option<ResMut<AutosaveState>>,
+    mut preferences: Option<ResMut<EditorPreferences>>,
+    mut status: Option<ResMut<SaveStatus>>,
+    mut watch: Option<ResMut<SceneWatch>>,
+) {
+    let mut finished = Vec::new();
+    tasks.0.retain_mut(|(path, task)| match block_on(poll_once(task)) {
+        Some(result) => {
+            finished.push((path.clone(), result));
+            false
         }
+        None => true,
+    });
 
-        if let Some(path) = &scene_info.file_path {
-            if let Ok(mut file) = File::create(path) {
-                let _ = file.write_all(saved_scene.0.as_bytes());
+    for (path, result) in finished {
+        let name = path.file_name().map_or_else(|| path.to_string_lossy(), |n| n.to_string_lossy()).into_owned();
+        let (message, is_error) = match result {
+            Ok(()) => {
                 info!("Scene saved to {:?}", path);
-            } else {
-                error!("Failed to create scene file at {:?}", path);
+                if let Some(autosave) = autosave.as_mut() {
+                    autosave.remove_recovery_files(Some(&path));
+                }
+                if let Some(preferences) = preferences.as_mut() {
+                    preferences.add_recent(&path);
+                }
+                // Our own write isn't an external change
+                if let Some(watch) = watch.as_mut()
+                    && scene_info.file_path.as_ref() == Some(&path)
+                {
+                    watch.mark_current(&path);
+                }
+                (format!("Saved {}", name), false)
             }
-        } else {
-            error!("Cannot save: No file path set in SceneInfo");
+            Err(e) => {
+                error!("Failed to save scene to {:?}: {}", path, e);
+                // The changes are still only in memory
+                scene_info.is_dirty = true;
+                (format!("Save failed: {}", e), true)
+            }
+        };
+        if let Some(status) = status.as_mut() {
+            **status = SaveStatus { message, is_error };
         }
     }
 }
 
-pub fn load_system(
-    mut commands: Commands,
-    mut scene_spawner: ResMut<SceneSpawner>,
-    query: Query<Entity, (Without<EditorRoot>, Without<ChildOf>, Without<bevy::window::PrimaryWindow>, Without<crate::editor::camera::EditorCamera>)>,
-    load_request: Option<Res<LoadRequest>>,
-    mut scene_info: ResMut<SceneInfo>,
-    type_registry: Res<AppTypeRegistry>,
-    mut dynamic_scene_assets: ResMut<Assets<DynamicScene>>,
+pub fn update_save_status_text(
+    status: Res<SaveStatus>,
+    mut query: Query<(&mut Text, &mut TextColor), With<SaveStatusText>>,
 ) {
-    if load_request.is_none() {
+    if !status.is_changed() {
         return;
     }
-
-    // Need a path. Clone it to end the borrow of scene_info immediately.
-    let path = if let Some(p) = &scene_info.file_path {
-        p.clone()
-    } else {
-        error!("Cannot load: No file path set in SceneInfo");
-        commands.remove_resource::<LoadRequest>();
-        return;
-    };
-
-    // Clear current world
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
+    for (mut text, mut color) in query.iter_mut() {
+        **text = status.message.clone();
+        *color = if status.is_error { Color::srgb(0.9, 0.3, 0.3) } else { TEXT_COLOR }.into();
     }
+}
 
-    // Manual deserialization to support absolute paths
-    let Ok(scene_ron) = std::fs::read_to_string(&path) else {
+/// Reads and deserializes a scene file, logging failures and skipped unknown types.
+pub fn read_scene_file(
+    path: &std::path::Path,
+    type_registry: &bevy::reflect::TypeRegistry,
+    migrations: &SceneMigrations,
+    mode: scene_format::SceneLoadMode,
+) -> Option<DynamicScene> {
+    let Ok(bytes) = std::fs::read(path) else {
         error!("Failed to read scene file: {:?}", path);
-        commands.remove_resource::<LoadRequest>();
-        return;
+        return None;
     };
+    parse_scene_file(&bytes, path, type_registry, migrations, mode)
+}
 
-    let mut deserializer = ron::Deserializer::from_str(&scene_ron).expect("Failed to create deserializer");
-    let type_registry = type_registry.read();
-    let scene_deserializer = bevy::scene::serde::SceneDeserializer {
-        type_registry: &type_registry,
+/// Deserializes the contents of the scene file at `path` in either format, logging failures and
+/// skipped unknown types. Prefab links are resolved against the file's directory.
+pub fn parse_scene_file(
+    bytes: &[u8],
+    path: &std::path::Path,
+    type_registry: &bevy::reflect::TypeRegistry,
+    migrations: &SceneMigrations,
+    mode: scene_format::SceneLoadMode,
+) -> Option<DynamicScene> {
+    let loaded = match scene_format::deserialize_scene_file(bytes, type_registry, migrations, mode) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!("Failed to deserialize scene from {:?}: {}", path, e);
+            return None;
+        }
     };
 
-    let Ok(dynamic_scene) = serde::de::DeserializeSeed::deserialize(scene_deserializer, &mut deserializer) else {
-        error!("Failed to deserialize scene from {:?}", path);
-        commands.remove_resource::<LoadRequest>();
+    for (type_path, count) in &loaded.unknown_types {
+        warn!("Skipped unknown type {} ({} occurrence(s)); it will be preserved on save", type_path, count);
+    }
+    for type_path in &loaded.dropped_resources {
+        warn!("Dropped unknown resource {}; it will not be written back on save", type_path);
+    }
+    let mut scene = loaded.scene;
+    crate::editor::prefab::resolve_prefab_links(&mut scene, path);
+    Some(scene)
+}
+
+/// [`read_scene_file`] with the world's registry, migrations and load mode.
+pub fn read_world_scene_file(
+    world: &World,
+    type_registry: &bevy::reflect::TypeRegistry,
+    path: &std::path::Path,
+) -> Option<DynamicScene> {
+    let mode = world.get_resource::<EditorConfig>().map(|c| c.scene_load_mode).unwrap_or_default();
+    match world.get_resource::<SceneMigrations>() {
+        Some(migrations) => read_scene_file(path, type_registry, migrations, mode),
+        None => read_scene_file(path, type_registry, &SceneMigrations::default(), mode),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn import_system(
+    mut commands: Commands,
+    mut scene_spawner: ResMut<SceneSpawner>,
+    import_request: Option<Res<ImportRequest>>,
+    mut scene_info: ResMut<SceneInfo>,
+    type_registry: Res<AppTypeRegistry>,
+    config: Res<EditorConfig>,
+    migrations: Res<SceneMigrations>,
+    mut dynamic_scene_assets: ResMut<Assets<DynamicScene>>,
+) {
+    let Some(request) = import_request else {
         return;
     };
+    commands.remove_resource::<ImportRequest>();
 
-    // Add to assets to get a handle (needed for scene spawner)
-    let scene_handle = dynamic_scene_assets.add(dynamic_scene);
-
-    // Spawn new scene
-    scene_spawner.spawn_dynamic(scene_handle);
+    let Some(mut scene) = read_scene_file(&request.path, &type_registry.read(), &migrations, config.scene_load_mode) else {
+        return;
+    };
+    // The imported entities get new ids in the current scene on the next save
+    scene_format::clear_scene_ids(&mut scene);
+
+    let scene_handle = dynamic_scene_assets.add(scene);
+    if request.under_parent {
+        let name = request
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map_or("Imported Scene", |n| n.trim_end_matches(".scn.ron").trim_end_matches(".scn.bin"));
+        let parent = commands.spawn((
+            Name::new(name.to_string()),
+            Transform::default(),
+            Visibility::default(),
+        )).id();
+        scene_spawner.spawn_dynamic_as_child(scene_handle, parent);
+    } else {
+        scene_spawner.spawn_dynamic(scene_handle);
+    }
 
-    commands.remove_resource::<LoadRequest>();
-    scene_info.is_dirty = false;
-    info!("Scene loaded from {:?}", path);
+    scene_info.is_dirty = true;
+    info!("Scene imported from {:?}", request.path);
 }
 
 pub fn update_window_title(
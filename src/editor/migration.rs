@@ -0,0 +1,345 @@
+use bevy::prelude::*;
+use ron::value::RawValue;
+use std::collections::BTreeMap;
+use super::scene_format::{BinaryScene, BinaryValue, RawScene, SceneFormatError, StructFields, SCENE_FORMAT_VERSION};
+
+type ValueTransform = Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+
+/// A single change applied to a scene file before its values go through reflection.
+pub enum MigrationStep {
+    /// Renames a component or resource type path, e.g. after a type moved between crates.
+    RenameType { from: String, to: String },
+    /// Renames a top-level field of a struct component or resource.
+    RenameField { type_path: String, from: String, to: String },
+    /// Rewrites the raw RON of every value stored under `type_path`.
+    TransformValue { type_path: String, transform: ValueTransform },
+}
+
+struct VersionedStep {
+    /// `None` for the editor's own steps, which follow the file's `version` header.
+    plugin: Option<String>,
+    version: u32,
+    step: MigrationStep,
+}
+
+/// Registry of scene migrations.
+///
+/// The editor's own steps are keyed by the format version that introduced them and compared with
+/// the file's `version` header. Games and plugins register their steps under their own name with
+/// a version counter of their own, compared with that name's entry in the file's `migrations`
+/// header (0 if it has none), so they never clash with the editor's format versions.
+///
+/// `load_system` applies every pending step: the editor's first, then each plugin's in version
+/// order. Saved scenes are stamped with [`SCENE_FORMAT_VERSION`] and
+/// [`SceneMigrations::plugin_versions`]. The default registry carries the renames for types Bevy
+/// moved out of `bevy_render`/`bevy_pbr`.
+#[derive(Resource)]
+pub struct SceneMigrations {
+    steps: Vec<VersionedStep>,
+}
+
+impl Default for SceneMigrations {
+    fn default() -> Self {
+        let mut migrations = Self::empty();
+        for (from, to) in [
+            ("bevy_render::view::visibility::Visibility", "bevy_camera::visibility::Visibility"),
+            ("bevy_render::view::visibility::InheritedVisibility", "bevy_camera::visibility::InheritedVisibility"),
+            ("bevy_render::view::visibility::ViewVisibility", "bevy_camera::visibility::ViewVisibility"),
+            ("bevy_render::view::visibility::VisibilityClass", "bevy_camera::visibility::VisibilityClass"),
+            ("bevy_render::primitives::Aabb", "bevy_camera::primitives::Aabb"),
+            ("bevy_render::primitives::CubemapFrusta", "bevy_camera::primitives::CubemapFrusta"),
+            ("bevy_render::primitives::Frustum", "bevy_camera::primitives::Frustum"),
+            ("bevy_render::camera::camera::Camera", "bevy_camera::camera::Camera"),
+            ("bevy_render::camera::projection::Projection", "bevy_camera::projection::Projection"),
+            ("bevy_core_pipeline::core_3d::camera_3d::Camera3d", "bevy_camera::components::Camera3d"),
+            ("bevy_pbr::light::point_light::PointLight", "bevy_light::point_light::PointLight"),
+            ("bevy_pbr::light::spot_light::SpotLight", "bevy_light::spot_light::SpotLight"),
+            ("bevy_pbr::light::directional_light::DirectionalLight", "bevy_light::directional_light::DirectionalLight"),
+        ] {
+            migrations.insert(None, 1, MigrationStep::RenameType { from: from.into(), to: to.into() });
+        }
+        migrations
+    }
+}
+
+impl SceneMigrations {
+    /// A registry without the built-in Bevy renames.
+    pub fn empty() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// The newest registered version of each plugin, written to the `migrations` header of saved
+    /// scenes.
+    pub fn plugin_versions(&self) -> BTreeMap<String, u32> {
+        let mut versions = BTreeMap::new();
+        for step in &self.steps {
+            if let Some(plugin) = &step.plugin {
+                let version = versions.entry(plugin.clone()).or_insert(0);
+                *version = step.version.max(*version);
+            }
+        }
+        versions
+    }
+
+    pub fn rename_type(
+        &mut self,
+        plugin: impl Into<String>,
+        version: u32,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> &mut Self {
+        self.add(plugin, version, MigrationStep::RenameType { from: from.into(), to: to.into() })
+    }
+
+    pub fn rename_field(
+        &mut self,
+        plugin: impl Into<String>,
+        version: u32,
+        type_path: impl Into<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> &mut Self {
+        self.add(plugin, version, MigrationStep::RenameField {
+            type_path: type_path.into(),
+            from: from.into(),
+            to: to.into(),
+        })
+    }
+
+    pub fn transform_value(
+        &mut self,
+        plugin: impl Into<String>,
+        version: u32,
+        type_path: impl Into<String>,
+        transform: impl Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.add(plugin, version, MigrationStep::TransformValue {
+            type_path: type_path.into(),
+            transform: Box::new(transform),
+        })
+    }
+
+    /// Registers `step` as version `version` of `plugin`'s migrations. Files without an entry for
+    /// `plugin` are at its version 0.
+    ///
+    /// # Panics
+    ///
+    /// If `version` is 0.
+    pub fn add(&mut self, plugin: impl Into<String>, version: u32, step: MigrationStep) -> &mut Self {
+        let plugin = plugin.into();
+        assert!(version > 0, "scene migration versions of `{}` must start at 1", plugin);
+        self.insert(Some(plugin), version, step)
+    }
+
+    fn insert(&mut self, plugin: Option<String>, version: u32, step: MigrationStep) -> &mut Self {
+        // The editor's steps sort first. Keep registration order within a version so steps can
+        // build on each other.
+        let index = self
+            .steps
+            .partition_point(|s| (&s.plugin, s.version) <= (&plugin, version));
+        self.steps.insert(index, VersionedStep { plugin, version, step });
+        self
+    }
+
+    /// Upgrades `scene` in place to [`SCENE_FORMAT_VERSION`] and [`Self::plugin_versions`].
+    pub fn migrate(&self, scene: &mut RawScene) -> Result<(), SceneFormatError> {
+        for step in self.pending_steps(scene.version, &scene.migrations) {
+            apply_step(step, &mut scene.resources)?;
+            for entity in scene.entities.values_mut() {
+                apply_step(step, &mut entity.components)?;
+            }
+        }
+        self.stamp(&mut scene.version, &mut scene.migrations);
+        Ok(())
+    }
+
+    /// [`Self::migrate`] for binary scenes. Type renames work as usual, but values can only be
+    /// rewritten while they are still RON, so any other value a step would change fails the load.
+    pub fn migrate_binary(&self, scene: &mut BinaryScene) -> Result<(), SceneFormatError> {
+        for step in self.pending_steps(scene.version, &scene.migrations) {
+            apply_binary_step(step, &mut scene.resources)?;
+            for components in scene.entities.values_mut() {
+                apply_binary_step(step, components)?;
+            }
+        }
+        self.stamp(&mut scene.version, &mut scene.migrations);
+        Ok(())
+    }
+
+    // The steps newer than the file's format and plugin versions, in order.
+    fn pending_steps<'a>(
+        &'a self,
+        version: u32,
+        plugin_versions: &'a BTreeMap<String, u32>,
+    ) -> impl Iterator<Item = &'a MigrationStep> {
+        if version > SCENE_FORMAT_VERSION {
+            warn!(
+                "Scene format version {} is newer than this editor supports ({}); loading anyway",
+                version, SCENE_FORMAT_VERSION
+            );
+        }
+        let registered = self.plugin_versions();
+        for (plugin, version) in plugin_versions {
+            if registered.get(plugin).is_some_and(|registered| version > registered) {
+                warn!("Scene was migrated by a newer version of `{}` ({}); loading anyway", plugin, version);
+            }
+        }
+        self.steps
+            .iter()
+            .filter(move |s| match &s.plugin {
+                None => s.version > version,
+                Some(plugin) => s.version > plugin_versions.get(plugin).copied().unwrap_or(0),
+            })
+            .map(|s| &s.step)
+    }
+
+    fn stamp(&self, version: &mut u32, plugin_versions: &mut BTreeMap<String, u32>) {
+        *version = (*version).max(SCENE_FORMAT_VERSION);
+        for (plugin, registered) in self.plugin_versions() {
+            let version = plugin_versions.entry(plugin).or_insert(0);
+            *version = registered.max(*version);
+        }
+    }
+}
+
+fn rename_type<V>(values: &mut BTreeMap<String, V>, from: &str, to: &str) {
+    if let Some(value) = values.remove(from) {
+        if values.contains_key(to) {
+            warn!("Migration dropped `{}`: `{}` is already present", from, to);
+        } else {
+            values.insert(to.to_string(), value);
+        }
+    }
+}
+
+fn apply_step(step: &MigrationStep, values: &mut BTreeMap<String, Box<RawValue>>) -> Result<(), SceneFormatError> {
+    match step {
+        MigrationStep::RenameType { from, to } => rename_type(values, from, to),
+        MigrationStep::RenameField { type_path, from, to } => {
+            if let Some(value) = values.get_mut(type_path) {
+                let renamed = rename_struct_field(value.get_ron(), from, to);
+                *value = parse_migrated(type_path, renamed)?;
+            }
+        }
+        MigrationStep::TransformValue { type_path, transform } => {
+            if let Some(value) = values.get_mut(type_path) {
+                let transformed = transform(value.get_ron()).map_err(|message| SceneFormatError::Value {
+                    type_path: type_path.clone(),
+                    message,
+                })?;
+                *value = parse_migrated(type_path, transformed)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_binary_step(step: &MigrationStep, values: &mut BTreeMap<String, BinaryValue>) -> Result<(), SceneFormatError> {
+    let type_path = match step {
+        MigrationStep::RenameType { from, to } => {
+            rename_type(values, from, to);
+            return Ok(());
+        }
+        MigrationStep::RenameField { type_path, .. } | MigrationStep::TransformValue { type_path, .. } => type_path,
+    };
+    match values.get_mut(type_path) {
+        Some(BinaryValue::Ron(ron)) => {
+            let mut raw = BTreeMap::from([(type_path.clone(), parse_migrated(type_path, ron.clone())?)]);
+            apply_step(step, &mut raw)?;
+            *ron = raw[type_path].get_ron().to_string();
+        }
+        Some(BinaryValue::Reflect(_)) => {
+            return Err(SceneFormatError::Value {
+                type_path: type_path.clone(),
+                message: "binary values can't be migrated; convert the scene to RON with the editor that saved it"
+                    .to_string(),
+            });
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+fn parse_migrated(type_path: &str, ron: String) -> Result<Box<RawValue>, SceneFormatError> {
+    RawValue::from_boxed_ron(ron.into_boxed_str()).map_err(|e| SceneFormatError::Value {
+        type_path: type_path.to_string(),
+        message: format!("migration produced invalid RON: {}", e),
+    })
+}
+
+/// Renames the top-level field `from` of a RON struct value, leaving nested values, strings and
+/// comments untouched.
+pub fn rename_struct_field(ron: &str, from: &str, to: &str) -> String {
+    let Some(fields) = StructFields::parse(ron) else {
+        return ron.to_string();
+    };
+
+    let renamed: Vec<String> = fields
+        .entries
+        .iter()
+        .map(|entry| match StructFields::field_name_range(entry) {
+            Some(range) if &entry[range.clone()] == from => {
+                format!("{}{}{}", &entry[..range.start], to, &entry[range.end..])
+            }
+            _ => entry.to_string(),
+        })
+        .collect();
+    let entries: Vec<&str> = renamed.iter().map(String::as_str).collect();
+    fields.rebuild(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_scene(header: &str) -> RawScene {
+        let input = format!(r#"({} entities: {{ 1: (components: {{ "my_game::Health": (hp: 7) }}) }})"#, header);
+        ron::from_str(&input).unwrap()
+    }
+
+    #[test]
+    fn test_plugin_versions_are_separate_from_the_format_version() {
+        let mut migrations = SceneMigrations::empty();
+        migrations.rename_field("my_game", 1, "my_game::Health", "hp", "current");
+        migrations.rename_type("other_plugin", 4, "other::Old", "other::New");
+        assert_eq!(migrations.plugin_versions(), BTreeMap::from([("my_game".to_string(), 1), ("other_plugin".to_string(), 4)]));
+
+        // A file already at the editor's format version still takes the plugin's steps
+        let mut scene = raw_scene(&format!("version: {},", SCENE_FORMAT_VERSION));
+        migrations.migrate(&mut scene).unwrap();
+        assert_eq!(scene.entities[&1].components["my_game::Health"].get_ron().trim(), "(current: 7)");
+        assert_eq!(scene.version, SCENE_FORMAT_VERSION);
+        assert_eq!(scene.migrations, migrations.plugin_versions());
+
+        // Steps the file has already taken don't run again
+        let mut scene = raw_scene(r#"version: 2, migrations: { "my_game": 1 },"#);
+        let mut failing = SceneMigrations::empty();
+        failing.transform_value("my_game", 1, "my_game::Health", |_| Err("should not run".to_string()));
+        assert!(failing.migrate(&mut scene).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "must start at 1")]
+    fn test_plugin_migration_versions_start_at_one() {
+        SceneMigrations::empty().rename_type("my_game", 0, "my_game::Old", "my_game::New");
+    }
+
+    #[test]
+    fn test_rename_struct_field_only_touches_top_level() {
+        let ron = r#"(hp: 1, nested: (hp: 2), label: "hp: 3", // hp: 4
+  hp_max: 5)"#;
+        assert_eq!(
+            rename_struct_field(ron, "hp", "current"),
+            r#"(current: 1, nested: (hp: 2), label: "hp: 3", // hp: 4
+  hp_max: 5)"#
+        );
+    }
+    #[test]
+    fn test_rename_struct_field_skips_raw_strings() {
+        assert_eq!(
+            rename_struct_field(r##"Health(note: r#"say "hp: 3""#, hp: 1)"##, "hp", "current"),
+            r##"Health(note: r#"say "hp: 3""#, current: 1)"##
+        );
+        assert_eq!(rename_struct_field("Health()", "hp", "current"), "Health()");
+    }
+}
@@ -1,21 +1,73 @@
 use bevy::prelude::*;
 
 pub mod camera;
+pub mod command_palette;
 pub mod components;
 pub mod hierarchy;
 pub mod inspector;
 pub mod menu;
 pub mod resources;
+pub mod scene_format;
 pub mod styles;
 pub mod ui;
 pub mod input;
+pub mod keymap;
 pub mod actions;
 pub mod log;
+pub mod migration;
+pub mod save_filter;
+pub mod prefab;
+pub mod primitives;
+pub mod autosave;
+pub mod dialog;
+pub mod templates;
+pub mod preferences;
+pub mod file_dialog;
+pub mod scene_asset;
+pub mod scene_tools;
+pub mod text_field;
 
 use resources::{EditorConfig, EditorState, InspectorUiState};
 use log::EditorLogs;
+use std::any::TypeId;
+use std::path::PathBuf;
 
-pub struct EditorPlugin;
+pub struct EditorPlugin {
+    /// Component types never written to saved scenes. Types registered with
+    /// [`save_filter::ReflectSkipSave`] are skipped as well.
+    pub save_deny_list: Vec<TypeId>,
+    /// File the [`preferences::EditorPreferences`] are read from and written to. `None` keeps them
+    /// in memory only.
+    pub preferences_path: Option<PathBuf>,
+    /// File the [`keymap::EditorKeymap`] is read from. `None` uses the default bindings.
+    pub keymap_path: Option<PathBuf>,
+    /// Where file dialogs come from. Ignored if a [`file_dialog::FileDialogs`] resource was
+    /// inserted before the plugin, e.g. a [`file_dialog::ScriptedFileDialogs`] in tests.
+    pub file_dialog: file_dialog::FileDialogBackend,
+    /// Scene opened on startup. A path that doesn't exist yet becomes the untouched scene's file,
+    /// created on the first save.
+    pub initial_scene: Option<PathBuf>,
+}
+
+impl Default for EditorPlugin {
+    fn default() -> Self {
+        Self {
+            save_deny_list: save_filter::runtime_components(),
+            preferences_path: Some(PathBuf::from(preferences::PREFERENCES_FILE)),
+            keymap_path: Some(PathBuf::from(keymap::KEYMAP_FILE)),
+            file_dialog: file_dialog::FileDialogBackend::default(),
+            initial_scene: None,
+        }
+    }
+}
+
+impl EditorPlugin {
+    /// Adds `T` to [`Self::save_deny_list`].
+    pub fn skip_save<T: 'static>(mut self) -> Self {
+        self.save_deny_list.push(TypeId::of::<T>());
+        self
+    }
+}
 
 impl Plugin for EditorPlugin {
     fn build(&self, app: &mut App) {
@@ -24,15 +76,69 @@ impl Plugin for EditorPlugin {
             app.add_plugins(log::log_plugin());
         }
 
+        if let Some(path) = &self.preferences_path {
+            app.insert_resource(preferences::PreferencesPath(path.clone()));
+        }
+
+        // Before Startup, so the recovery offer already sees the scene's path
+        if let Some(path) = &self.initial_scene {
+            app.insert_resource(menu::SceneInfo { file_path: Some(path.clone()), is_dirty: false });
+            if path.is_file() {
+                app.insert_resource(menu::LoadRequest::default());
+            } else {
+                warn!("Scene {:?} doesn't exist yet; it will be created on save", path);
+            }
+        }
+
+        if !app.world().contains_resource::<file_dialog::FileDialogs>() {
+            app.insert_resource(self.file_dialog.provider());
+        }
+        // Tests insert a text_field::MemoryClipboard first
+        if !app.world().contains_resource::<text_field::EditorClipboard>() {
+            app.init_resource::<text_field::EditorClipboard>();
+        }
+
+        if app.world().get_resource::<AssetServer>().is_some_and(|s| s.get_source(scene_asset::SCENE_SOURCE).is_err()) {
+            error!("Add scene_asset::SceneSourcePlugin before DefaultPlugins, or scenes outside assets/ won't load");
+        }
+        if app.get_added_plugins::<bevy::window::WindowPlugin>().iter().any(|plugin| plugin.close_when_requested) {
+            error!("Set WindowPlugin::close_when_requested to false, or windows close without asking about unsaved changes");
+        }
+
         app.init_resource::<EditorState>()
            .init_resource::<EditorConfig>()
 
            .init_resource::<InspectorUiState>()
+           .init_resource::<input::TypedText>()
+           .init_resource::<input::EditorKeys>()
+           .init_resource::<command_palette::EditorCommands>()
            .init_resource::<resources::IsResizing>()
            .init_resource::<EditorLogs>()
+           .init_resource::<migration::SceneMigrations>()
+           .init_resource::<autosave::AutosaveSettings>()
+           .init_resource::<autosave::AutosaveState>()
+           .init_resource::<scene_asset::SceneAssets>()
+           .init_resource::<scene_asset::SceneWatch>()
+           .init_resource::<prefab::PrefabCache>()
+           .init_asset::<scene_asset::SceneFile>()
+           .init_asset_loader::<scene_asset::SceneFileLoader>()
+           .register_type::<scene_format::UnknownComponents>()
+           .register_type::<scene_format::SceneId>()
+           .register_type::<save_filter::EditorOnly>()
+           .register_type::<prefab::PrefabLink>()
+           .register_type::<primitives::PrimitiveMesh>()
+           .insert_resource(self.preferences_path.as_deref().map(preferences::EditorPreferences::load).unwrap_or_default())
+           .insert_resource(self.keymap_path.as_deref().map(keymap::EditorKeymap::load).unwrap_or_default())
+           .insert_resource(save_filter::SaveFilter {
+                denied: self.save_deny_list.iter().copied().collect(),
+           })
+           .add_message::<file_dialog::FileDialogResult>()
+           .add_observer(save_filter::propagate_editor_only_to_child)
+           .add_observer(save_filter::propagate_editor_only_to_descendants)
            .add_systems(Startup, (
                 ui::setup_editor_ui,
                 camera::setup_editor_cameras,
+                autosave::offer_recovery_system,
             ))
            .add_systems(Update, (
                 camera::editor_camera_controls,
@@ -43,25 +149,65 @@ impl Plugin for EditorPlugin {
                 hierarchy::handle_hierarchy_actions,
                 menu::handle_file_menu_button,
                 menu::menu_action_system,
+                menu::handle_window_close_requested,
             ))
            .add_systems(Update, (
-                menu::load_system,
+                scene_asset::load_system,
+                scene_asset::finish_scene_load.after(scene_asset::load_system),
+                scene_asset::watch_scene_file,
+                menu::import_system,
                 input::text_input_system,
                 actions::handle_delete_entity,
                 actions::handle_add_component_toggle,
                 actions::handle_remove_component,
                 actions::handle_add_component_confirm,
+                actions::handle_revert_override,
+                prefab::sync_new_prefab_instances,
+                primitives::build_primitive_meshes,
+                dialog::dialog_button_system,
+                menu::update_recent_scenes_menu,
+                menu::recent_scenes_menu_system,
+           ))
+           .add_systems(Update, (
+                file_dialog::poll_file_dialogs,
+                file_dialog::file_browser_system.after(input::text_input_system),
+                file_dialog::update_file_browser_list,
+                menu::handle_file_dialog_results.after(file_dialog::poll_file_dialogs),
                 log::transfer_logs_system,
                 log::log_panel_ui_system,
+                keymap::toggle_shortcuts_panel,
+                keymap::update_shortcuts_panel,
+                command_palette::command_palette_shortcut,
+                command_palette::command_palette_system.before(input::text_input_system),
+                camera::frame_selection_shortcut,
+                camera::animate_camera_framing.after(camera::editor_camera_controls),
            ))
            .init_resource::<menu::LastSavedScene>()
            .init_resource::<menu::SceneInfo>()
-           .add_systems(Update, inspector::inspector_ui_system)
-           .add_systems(Update, camera::editor_camera_controls)
-           .add_systems(Update, menu::update_window_title)
+           .init_resource::<menu::SaveTasks>()
+           .init_resource::<menu::SaveStatus>()
+           .add_systems(PreUpdate, (
+                input::collect_typed_text,
+                input::route_keyboard_input,
+           ).after(bevy::input::InputSystems))
+           .add_systems(Update, input::update_ime_system)
+           .add_systems(Update, (
+                inspector::inspector_ui_system,
+                inspector::component_search_system
+                    .after(inspector::inspector_ui_system)
+                    .after(input::text_input_system),
+                text_field::render_text_fields
+                    .after(inspector::component_search_system)
+                    .after(input::text_input_system),
+           ))
+           .add_systems(Update, (menu::update_window_title, menu::update_save_status_text))
            .add_systems(PostUpdate, (
                 menu::save_system,
-                menu::save_to_file_system,
+                menu::save_to_file_system.after(menu::save_system),
+                menu::poll_save_tasks.after(menu::save_to_file_system),
+                autosave::autosave_system.after(menu::poll_save_tasks),
+                menu::run_pending_action.after(autosave::autosave_system).after(menu::poll_save_tasks),
+                preferences::save_preferences_system.after(menu::poll_save_tasks),
                 // Synchronize viewport AFTER UI Layout is calculated
                 camera::sync_camera_viewport.after(bevy::ui::UiSystems::Layout),
            ));
@@ -0,0 +1,477 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::Command;
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::reflect::{GetPath, TypeRegistry};
+use bevy::scene::DynamicSceneBuilder;
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use super::menu::{read_world_scene_file, write_atomic, SceneInfo};
+use super::migration::SceneMigrations;
+use super::resources::{EditorConfig, EditorState};
+use super::save_filter::{EditorOnly, SaveFilter};
+use super::scene_format::{self, SceneId, SceneSaveOptions, UnknownComponents};
+
+/// A field edited on a prefab instance. Overridden fields keep their value when the prefab changes.
+#[derive(Reflect, Clone, Debug, Default, PartialEq)]
+pub struct PrefabOverride {
+    /// Type path of the component.
+    pub component: String,
+    /// Reflect path of the field inside the component, e.g. `translation` or `translation.x`.
+    pub field: String,
+}
+
+/// Links an entity to the prefab file it was instantiated from.
+///
+/// Every entity of an instance carries one, and it is saved with the scene, so instances keep
+/// tracking their prefab across sessions.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct PrefabLink {
+    /// Absolute path of the prefab file. Scene files store it relative to the scene's directory,
+    /// see [`relativize_prefab_links`].
+    pub source: String,
+    /// Scene id of the matching entity in the prefab file.
+    pub node: u32,
+    pub overrides: Vec<PrefabOverride>,
+}
+
+impl PrefabLink {
+    /// Whether `field` of `component`, or anything below it, is overridden.
+    pub fn is_overridden(&self, component: &str, field: &str) -> bool {
+        self.overrides
+            .iter()
+            .any(|o| o.component == component && is_same_or_child_path(&o.field, field))
+    }
+
+    pub fn add_override(&mut self, component: &str, field: &str) {
+        if !self.overrides.iter().any(|o| o.component == component && o.field == field) {
+            self.overrides.push(PrefabOverride {
+                component: component.to_string(),
+                field: field.to_string(),
+            });
+        }
+    }
+
+    /// Removes the override of `field` and of everything below it.
+    pub fn remove_override(&mut self, component: &str, field: &str) {
+        self.overrides
+            .retain(|o| o.component != component || !is_same_or_child_path(&o.field, field));
+    }
+}
+
+fn is_same_or_child_path(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('['))
+}
+
+// Components owned by the instance itself rather than copied from the prefab. Hierarchy changes
+// inside a prefab are not propagated to existing instances.
+fn is_instance_component(type_id: TypeId) -> bool {
+    [
+        TypeId::of::<SceneId>(),
+        TypeId::of::<PrefabLink>(),
+        TypeId::of::<UnknownComponents>(),
+        TypeId::of::<ChildOf>(),
+        TypeId::of::<Children>(),
+    ]
+    .contains(&type_id)
+}
+
+/// The [`PrefabLink::source`] of the prefab file at `path`.
+pub fn link_source(path: &Path) -> String {
+    normalize(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+// `path` relative to the directory `base`, with `/` separators so scene files stay portable.
+// `None` if they don't share a root, e.g. on different drives.
+fn relative_path(path: &Path, base: &Path) -> Option<String> {
+    let path = normalize(&std::path::absolute(path).ok()?);
+    let base = normalize(&std::path::absolute(base).ok()?);
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    if path.first() != base.first() {
+        return None;
+    }
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let parts: Vec<String> = std::iter::repeat_n("..".to_string(), base.len() - common)
+        .chain(path[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()))
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn scene_dir(scene_path: &Path) -> &Path {
+    scene_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."))
+}
+
+fn map_prefab_links(scene: &mut DynamicScene, map: impl Fn(&str) -> String) {
+    for entity in &mut scene.entities {
+        for component in &mut entity.components {
+            if component.get_represented_type_info().is_none_or(|info| info.type_id() != TypeId::of::<PrefabLink>()) {
+                continue;
+            }
+            let Some(mut link) = PrefabLink::from_reflect(component.as_partial_reflect()) else {
+                continue;
+            };
+            link.source = map(&link.source);
+            *component = Box::new(link).into_partial_reflect();
+        }
+    }
+}
+
+/// Rewrites the prefab links of a scene about to be saved to `scene_path` relative to its
+/// directory, so the scene and its prefabs can be moved together.
+pub fn relativize_prefab_links(scene: &mut DynamicScene, scene_path: &Path) {
+    let dir = scene_dir(scene_path);
+    map_prefab_links(scene, |source| relative_path(Path::new(source), dir).unwrap_or_else(|| source.to_string()));
+}
+
+/// Turns the relative prefab links of a scene read from `scene_path` back into absolute paths.
+pub fn resolve_prefab_links(scene: &mut DynamicScene, scene_path: &Path) {
+    let dir = scene_dir(scene_path);
+    map_prefab_links(scene, |source| {
+        if Path::new(source).is_relative() {
+            link_source(&dir.join(source))
+        } else {
+            source.to_string()
+        }
+    });
+}
+
+/// Prefab files parsed for [`sync_prefab_instances`], read again when their modification time
+/// changes.
+#[derive(Resource, Default)]
+pub struct PrefabCache(HashMap<PathBuf, (Option<SystemTime>, DynamicScene)>);
+
+impl PrefabCache {
+    fn get(&mut self, world: &World, registry: &TypeRegistry, path: &Path) -> Option<&DynamicScene> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let fresh = modified.is_some() && self.0.get(path).is_some_and(|(time, _)| *time == modified);
+        if !fresh {
+            self.0.remove(path);
+            let prefab = read_world_scene_file(world, registry, path)?;
+            self.0.insert(path.to_path_buf(), (modified, prefab));
+        }
+        self.0.get(path).map(|(_, prefab)| prefab)
+    }
+
+    /// Drops the cached contents of `path`, e.g. after the editor wrote it.
+    pub fn invalidate(&mut self, path: &Path) {
+        self.0.remove(path);
+    }
+}
+
+fn mark_dirty(world: &mut World) {
+    if let Some(mut info) = world.get_resource_mut::<SceneInfo>() {
+        info.is_dirty = true;
+    }
+}
+
+/// Saves `root` and its descendants as a prefab file and turns them into an instance of it.
+///
+/// If they already are an instance of the same file, their prefab nodes are kept and every other
+/// instance is updated with the new contents.
+pub struct SavePrefabCommand {
+    pub root: Entity,
+    pub path: PathBuf,
+}
+
+impl Command for SavePrefabCommand {
+    fn apply(self, world: &mut World) {
+        let source = link_source(&self.path);
+
+        let mut entities = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(entity) = stack.pop() {
+            if world.get::<EditorOnly>(entity).is_some() {
+                continue;
+            }
+            entities.push(entity);
+            if let Some(children) = world.get::<Children>(entity) {
+                stack.extend(children.iter().rev());
+            }
+        }
+
+        // Keep the nodes of an existing instance so other instances still match up
+        let mut used = HashSet::new();
+        let mut nodes: HashMap<Entity, u32> = HashMap::default();
+        for &entity in &entities {
+            if let Some(link) = world.get::<PrefabLink>(entity)
+                && link.source == source
+                && used.insert(link.node)
+            {
+                nodes.insert(entity, link.node);
+            }
+        }
+        let first = used.iter().max().map_or(1, |max| max + 1);
+        let new_nodes: Vec<Entity> = entities.iter().copied().filter(|e| !nodes.contains_key(e)).collect();
+        for (node, entity) in (first..).zip(new_nodes) {
+            nodes.insert(entity, node);
+        }
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let component_filter = world
+            .get_resource::<SaveFilter>()
+            .cloned()
+            .unwrap_or_default()
+            .scene_filter(&registry)
+            .deny_by_id(TypeId::of::<SceneId>())
+            .deny_by_id(TypeId::of::<PrefabLink>());
+        let mut scene = DynamicSceneBuilder::from_world(world)
+            .with_component_filter(component_filter)
+            .extract_entities(entities.iter().copied())
+            .build();
+
+        for dynamic_entity in &mut scene.entities {
+            if dynamic_entity.entity == self.root {
+                // The prefab root is placed by whoever instantiates it
+                dynamic_entity.components.retain(|c| {
+                    c.get_represented_type_info().is_none_or(|info| info.type_id() != TypeId::of::<ChildOf>())
+                });
+            }
+            let node = nodes[&dynamic_entity.entity];
+            dynamic_entity.components.push(Box::new(SceneId(node)).into_partial_reflect());
+        }
+
+        let options = SceneSaveOptions {
+            version: scene_format::SCENE_FORMAT_VERSION,
+            migrations: world
+                .get_resource::<SceneMigrations>()
+                .map(SceneMigrations::plugin_versions)
+                .unwrap_or_default(),
+            omit_default_fields: world
+                .get_resource::<EditorConfig>()
+                .is_some_and(|config| config.omit_default_fields),
+        };
+        let serialized = match scene_format::serialize_scene(&scene, &registry, &options) {
+            Ok(serialized) => serialized,
+            Err(e) => {
+                error!("Failed to serialize prefab: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = write_atomic(&self.path, serialized.as_bytes()) {
+            error!("Failed to write prefab to {:?}: {}", self.path, e);
+            return;
+        }
+        drop(registry);
+        if let Some(mut cache) = world.get_resource_mut::<PrefabCache>() {
+            cache.invalidate(Path::new(&source));
+        }
+        info!("Prefab saved to {:?}", self.path);
+
+        // The saved values are now the prefab's own, so this instance has no overrides left
+        for (&entity, &node) in &nodes {
+            world.entity_mut(entity).insert(PrefabLink {
+                source: source.clone(),
+                node,
+                overrides: Vec::new(),
+            });
+        }
+
+        let mut others = world.query::<(Entity, &PrefabLink)>();
+        let others: Vec<Entity> = others
+            .iter(world)
+            .filter(|(entity, link)| link.source == source && !nodes.contains_key(entity))
+            .map(|(entity, _)| entity)
+            .collect();
+        sync_prefab_instances(world, &source, &others);
+        mark_dirty(world);
+    }
+}
+
+/// Spawns a new instance of a prefab file, optionally as a child of `parent`, and selects it.
+pub struct InstantiatePrefabCommand {
+    pub path: PathBuf,
+    pub parent: Option<Entity>,
+}
+
+impl Command for InstantiatePrefabCommand {
+    fn apply(self, world: &mut World) {
+        let source = link_source(&self.path);
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let Some(mut prefab) = read_world_scene_file(world, &registry.read(), &self.path) else {
+            return;
+        };
+
+        let nodes: Vec<(Entity, u32)> = prefab
+            .entities
+            .iter()
+            .filter_map(|e| scene_format::scene_id(e).map(|id| (e.entity, id.0)))
+            .collect();
+        scene_format::clear_scene_ids(&mut prefab);
+
+        let mut entity_map = EntityHashMap::default();
+        if let Err(e) = prefab.write_to_world(world, &mut entity_map) {
+            error!("Failed to instantiate prefab {:?}: {}", self.path, e);
+            return;
+        }
+
+        let mut root = None;
+        for (file_entity, node) in nodes {
+            let Some(&entity) = entity_map.get(&file_entity) else {
+                continue;
+            };
+            let mut entity_mut = world.entity_mut(entity);
+            entity_mut.insert(PrefabLink {
+                source: source.clone(),
+                node,
+                overrides: Vec::new(),
+            });
+            if !entity_mut.contains::<ChildOf>() {
+                root = Some(entity);
+                if let Some(parent) = self.parent {
+                    entity_mut.insert(ChildOf(parent));
+                }
+            }
+        }
+
+        if let Some(mut state) = world.get_resource_mut::<EditorState>() {
+            state.selected_entity = root;
+        }
+        mark_dirty(world);
+        info!("Prefab instantiated from {:?}", self.path);
+    }
+}
+
+/// Re-applies the prefab at `source` to `targets`, keeping each instance's overridden fields.
+pub fn sync_prefab_instances(world: &mut World, source: &str, targets: &[Entity]) {
+    if targets.is_empty() {
+        return;
+    }
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    world.init_resource::<PrefabCache>();
+    world.resource_scope(|world, mut cache: Mut<PrefabCache>| {
+        let Some(prefab) = cache.get(world, &registry, Path::new(source)) else {
+            return;
+        };
+        let nodes: HashMap<u32, &bevy::scene::DynamicEntity> = prefab
+            .entities
+            .iter()
+            .filter_map(|e| scene_format::scene_id(e).map(|id| (id.0, e)))
+            .collect();
+
+        for &target in targets {
+            let Some(link) = world.get::<PrefabLink>(target).cloned() else {
+                continue;
+            };
+            let Some(node) = nodes.get(&link.node) else {
+                continue;
+            };
+            for component in &node.components {
+                apply_prefab_component(world, target, component.as_ref(), &link, &registry);
+            }
+        }
+    });
+}
+
+fn apply_prefab_component(
+    world: &mut World,
+    entity: Entity,
+    value: &dyn PartialReflect,
+    link: &PrefabLink,
+    registry: &TypeRegistry,
+) {
+    let Some(type_info) = value.get_represented_type_info() else {
+        return;
+    };
+    if is_instance_component(type_info.type_id()) {
+        return;
+    }
+    let Some(reflect_component) = registry
+        .get(type_info.type_id())
+        .and_then(|registration| registration.data::<ReflectComponent>())
+    else {
+        return;
+    };
+    let Ok(mut merged) = value.reflect_clone() else {
+        return;
+    };
+
+    // Carry the instance's overridden fields over into the prefab value
+    if let Some(current) = reflect_component.reflect(world.entity(entity)) {
+        for o in link.overrides.iter().filter(|o| o.component == type_info.type_path()) {
+            if let Ok(field) = current.reflect_path(o.field.as_str())
+                && let Ok(target) = merged.reflect_path_mut(o.field.as_str())
+            {
+                target.apply(field);
+            }
+        }
+    }
+
+    let mut entity_mut = world.entity_mut(entity);
+    if reflect_component.contains(&entity_mut) {
+        reflect_component.apply(entity_mut, merged.as_partial_reflect());
+    } else {
+        reflect_component.insert(&mut entity_mut, merged.as_partial_reflect(), registry);
+    }
+}
+
+/// Restores one field of a prefab instance to the prefab's value.
+pub struct RevertOverrideCommand {
+    pub entity: Entity,
+    pub component: String,
+    pub field: String,
+}
+
+impl Command for RevertOverrideCommand {
+    fn apply(self, world: &mut World) {
+        let Some(mut link) = world.get_mut::<PrefabLink>(self.entity) else {
+            return;
+        };
+        link.remove_override(&self.component, &self.field);
+        let source = link.source.clone();
+        sync_prefab_instances(world, &source, &[self.entity]);
+        mark_dirty(world);
+    }
+}
+
+/// Brings prefab instances spawned from a scene file up to date with their prefab.
+pub fn sync_new_prefab_instances(
+    query: Query<(Entity, &PrefabLink), Added<PrefabLink>>,
+    mut commands: Commands,
+) {
+    let mut by_source: BTreeMap<String, Vec<Entity>> = BTreeMap::new();
+    for (entity, link) in query.iter() {
+        by_source.entry(link.source.clone()).or_default().push(entity);
+    }
+    for (source, entities) in by_source {
+        commands.queue(move |world: &mut World| sync_prefab_instances(world, &source, &entities));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_paths_use_forward_slashes() {
+        let root = std::path::absolute("project").unwrap();
+        let prefab = root.join("prefabs").join("crate.prefab.ron");
+        assert_eq!(relative_path(&prefab, &root.join("levels")).as_deref(), Some("../prefabs/crate.prefab.ron"));
+        assert_eq!(relative_path(&prefab, &root.join("prefabs")).as_deref(), Some("crate.prefab.ron"));
+        assert_eq!(normalize(&root.join("levels").join("..").join(".").join("prefabs")), root.join("prefabs"));
+        assert_eq!(scene_dir(Path::new("level.scn.ron")), Path::new("."));
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default location of the preferences file, relative to the working directory.
+pub const PREFERENCES_FILE: &str = "editor_preferences.ron";
+
+/// How many scenes the Recent Scenes menu remembers.
+pub const MAX_RECENT_SCENES: usize = 10;
+
+/// Per-user editor settings kept between sessions in a RON file.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct EditorPreferences {
+    /// Most recently opened or saved scenes first.
+    pub recent_scenes: Vec<PathBuf>,
+}
+
+impl EditorPreferences {
+    /// Reads the preferences file, falling back to the defaults when it is missing or invalid.
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match ron::from_str::<Self>(&text) {
+            Ok(mut preferences) => {
+                preferences.prune_missing();
+                preferences
+            }
+            Err(e) => {
+                warn!("Ignoring invalid preferences file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let config = ron::ser::PrettyConfig::new().indentor("  ".to_string()).new_line("\n".to_string());
+        let text = ron::ser::to_string_pretty(self, config).map_err(std::io::Error::other)?;
+        std::fs::write(path, text)
+    }
+
+    /// Moves `path` to the front of the recent scenes.
+    pub fn add_recent(&mut self, path: &Path) {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        self.recent_scenes.retain(|p| *p != path);
+        self.recent_scenes.insert(0, path);
+        self.recent_scenes.truncate(MAX_RECENT_SCENES);
+    }
+
+    /// Drops recent scenes whose file no longer exists. Returns whether any were removed.
+    pub fn prune_missing(&mut self) -> bool {
+        let len = self.recent_scenes.len();
+        self.recent_scenes.retain(|p| p.is_file());
+        self.recent_scenes.len() != len
+    }
+}
+
+/// Where [`EditorPreferences`] are written, from [`EditorPlugin::preferences_path`](super::EditorPlugin::preferences_path).
+/// Without it preferences only last for the session.
+#[derive(Resource, Clone, Debug)]
+pub struct PreferencesPath(pub PathBuf);
+
+/// Writes the preferences file whenever [`EditorPreferences`] change.
+pub fn save_preferences_system(preferences: Res<EditorPreferences>, path: Option<Res<PreferencesPath>>) {
+    if !preferences.is_changed() || preferences.is_added() {
+        return;
+    }
+    let Some(path) = path else {
+        return;
+    };
+    if let Err(e) = preferences.save(&path.0) {
+        error!("Failed to write preferences to {:?}: {}", path.0, e);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+/// Shape of a [`PrimitiveMesh`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Default)]
+#[reflect(Default)]
+pub enum PrimitiveShape {
+    #[default]
+    Cube,
+    Sphere,
+    /// A flat plane facing up, `size` wide along X and Z.
+    Plane { size: Vec2 },
+}
+
+impl PrimitiveShape {
+    pub fn mesh(self) -> Mesh {
+        match self {
+            PrimitiveShape::Cube => Cuboid::default().into(),
+            PrimitiveShape::Sphere => Sphere::default().into(),
+            PrimitiveShape::Plane { size } => Plane3d::default().mesh().size(size.x, size.y).into(),
+        }
+    }
+}
+
+/// A mesh the editor builds from a shape and a color.
+///
+/// Mesh and material handles aren't saved with the scene, so entities spawned from primitives keep
+/// this instead and [`build_primitive_meshes`] turns it back into a `Mesh3d` and
+/// `MeshMaterial3d` whenever it is added or changed, including after a load.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct PrimitiveMesh {
+    pub shape: PrimitiveShape,
+    pub color: Color,
+}
+
+impl Default for PrimitiveMesh {
+    fn default() -> Self {
+        Self {
+            shape: PrimitiveShape::default(),
+            color: Color::srgb(0.8, 0.8, 0.8),
+        }
+    }
+}
+
+/// Gives entities with a new or edited [`PrimitiveMesh`] a matching mesh and material. Without a
+/// renderer (e.g. headless) nothing is built.
+pub fn build_primitive_meshes(
+    mut commands: Commands,
+    primitives: Query<(Entity, &PrimitiveMesh), Changed<PrimitiveMesh>>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    let (Some(mut meshes), Some(mut materials)) = (meshes, materials) else {
+        return;
+    };
+    for (entity, primitive) in &primitives {
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(primitive.shape.mesh())),
+            MeshMaterial3d(materials.add(primitive.color)),
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-
+use super::scene_format::SceneLoadMode;
 
 #[derive(Resource, Default)]
 pub struct EditorState {
@@ -10,6 +10,11 @@ pub struct EditorState {
 #[derive(Resource, Default)]
 pub struct EditorConfig {
     pub show_editor: bool,
+    pub scene_load_mode: SceneLoadMode,
+    /// Leave struct fields that match the type's `Default` out of saved scenes.
+    pub omit_default_fields: bool,
+    /// Spawn imported scenes under a new entity named after the file.
+    pub import_under_parent: bool,
 }
 
 #[derive(Resource, Default)]
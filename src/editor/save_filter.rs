@@ -0,0 +1,132 @@
+use bevy::camera::primitives::{Aabb, CascadesFrusta, CubemapFrusta, Frustum};
+use bevy::camera::visibility::{
+    CascadesVisibleEntities, CubemapVisibleEntities, VisibilityClass, VisibleEntities, VisibleMeshEntities,
+};
+use bevy::ecs::observer::Observer;
+use bevy::input::gamepad::Gamepad;
+use bevy::picking::pointer::PointerId;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::reflect::{FromType, TypeRegistry};
+use bevy::render::sync_world::{RenderEntity, SyncToRenderWorld};
+use bevy::scene::SceneFilter;
+use bevy::transform::components::TransformTreeChanged;
+use bevy::window::Monitor;
+use std::any::TypeId;
+
+/// Type data for components and resources that are never written to saved scenes, usually
+/// because the engine recomputes them at runtime.
+///
+/// Add it with `#[reflect(SkipSave)]`, or with `app.register_type_data::<T, ReflectSkipSave>()`
+/// for types from other crates.
+#[derive(Clone)]
+pub struct ReflectSkipSave;
+
+impl<T> FromType<T> for ReflectSkipSave {
+    fn from_type() -> Self {
+        Self
+    }
+}
+
+/// Marks an entity owned by the editor (UI, editor cameras). It is never saved and never cleared
+/// by a scene load.
+///
+/// Tag the root of an editor-owned tree when it is spawned; the editor's observers copy the marker
+/// onto every descendant, so the save filter never has to walk up the hierarchy.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct EditorOnly;
+
+/// Entities that belong to the scene: everything except editor-owned entities and the ones the
+/// engine spawns for windows, monitors, pointers, gamepads and observers.
+pub type SceneEntityFilter = (
+    Without<EditorOnly>,
+    Without<Window>,
+    Without<Monitor>,
+    Without<PointerId>,
+    Without<Gamepad>,
+    Without<Observer>,
+);
+
+/// Tags an entity attached under an [`EditorOnly`] parent.
+pub fn propagate_editor_only_to_child(
+    insert: On<Insert, ChildOf>,
+    parents: Query<&ChildOf>,
+    editor_only: Query<(), With<EditorOnly>>,
+    mut commands: Commands,
+) {
+    let Ok(child_of) = parents.get(insert.entity) else {
+        return;
+    };
+    if editor_only.contains(child_of.parent()) && !editor_only.contains(insert.entity) {
+        commands.entity(insert.entity).insert(EditorOnly);
+    }
+}
+
+/// Tags the existing descendants of an entity that becomes [`EditorOnly`].
+pub fn propagate_editor_only_to_descendants(
+    add: On<Add, EditorOnly>,
+    children: Query<&Children>,
+    editor_only: Query<(), With<EditorOnly>>,
+    mut commands: Commands,
+) {
+    for descendant in children.iter_descendants(add.entity) {
+        if !editor_only.contains(descendant) {
+            commands.entity(descendant).insert(EditorOnly);
+        }
+    }
+}
+
+/// Component types left out of saved scenes, from [`EditorPlugin::save_deny_list`](super::EditorPlugin::save_deny_list).
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SaveFilter {
+    pub denied: HashSet<TypeId>,
+}
+
+impl SaveFilter {
+    pub fn deny<T: 'static>(&mut self) -> &mut Self {
+        self.denied.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// The component filter used when extracting a scene: the deny-list plus every type
+    /// registered with [`ReflectSkipSave`].
+    pub fn scene_filter(&self, registry: &TypeRegistry) -> SceneFilter {
+        let skipped = registry
+            .iter_with_data::<ReflectSkipSave>()
+            .map(|(registration, _)| registration.type_id());
+        self.denied
+            .iter()
+            .copied()
+            .chain(skipped)
+            .fold(SceneFilter::allow_all(), SceneFilter::deny_by_id)
+    }
+}
+
+/// Components Bevy derives at runtime or that only hold handles to runtime-created assets.
+/// This is the default [`EditorPlugin::save_deny_list`](super::EditorPlugin::save_deny_list).
+pub fn runtime_components() -> Vec<TypeId> {
+    vec![
+        // Recomputed from `Transform`, `Visibility` and `ChildOf` every frame. `Children` stays:
+        // scene spawning skips relationship hooks and expects both sides in the file.
+        TypeId::of::<GlobalTransform>(),
+        TypeId::of::<TransformTreeChanged>(),
+        TypeId::of::<InheritedVisibility>(),
+        TypeId::of::<ViewVisibility>(),
+        TypeId::of::<VisibilityClass>(),
+        // Culling and render-world bookkeeping.
+        TypeId::of::<Aabb>(),
+        TypeId::of::<Frustum>(),
+        TypeId::of::<CubemapFrusta>(),
+        TypeId::of::<CascadesFrusta>(),
+        TypeId::of::<VisibleEntities>(),
+        TypeId::of::<VisibleMeshEntities>(),
+        TypeId::of::<CubemapVisibleEntities>(),
+        TypeId::of::<CascadesVisibleEntities>(),
+        TypeId::of::<RenderEntity>(),
+        TypeId::of::<SyncToRenderWorld>(),
+        // Handles to meshes and materials built in code can't be written to a file.
+        TypeId::of::<Mesh3d>(),
+        TypeId::of::<MeshMaterial3d<StandardMaterial>>(),
+    ]
+}
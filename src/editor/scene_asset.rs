@@ -0,0 +1,322 @@
+use bevy::prelude::*;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::{AssetSourceBuilder, AssetSourceId, Reader};
+use bevy::asset::{AssetLoader, AssetPath, LoadContext};
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::Command;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use super::autosave::offer_recovery;
+use super::dialog::{spawn_dialog, DialogChoice, ModalDialog};
+use super::menu::{parse_scene_file, LoadRequest, SaveTasks, SceneInfo};
+use super::migration::SceneMigrations;
+use super::preferences::EditorPreferences;
+use super::resources::{EditorConfig, EditorState};
+use super::save_filter::SceneEntityFilter;
+use super::scene_format::{scene_ids, SceneId, BINARY_SCENE_EXTENSION};
+
+/// Asset source that resolves paths from the filesystem root, so scenes can be opened from
+/// anywhere rather than only from `assets/`.
+pub const SCENE_SOURCE: &str = "editor_scene";
+
+/// Registers the [`SCENE_SOURCE`] asset source. Asset sources have to exist before `AssetPlugin`
+/// is built, so add this plugin before `DefaultPlugins`.
+pub struct SceneSourcePlugin;
+
+impl Plugin for SceneSourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            SCENE_SOURCE,
+            AssetSourceBuilder::new(|| Box::new(FileAssetReader::new("/"))),
+        );
+    }
+}
+
+/// The raw contents of a scene file. The editor deserializes it itself so migrations and the
+/// lenient/strict load mode apply.
+#[derive(Asset, TypePath, Debug)]
+pub struct SceneFile {
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default, TypePath)]
+pub struct SceneFileLoader;
+
+impl AssetLoader for SceneFileLoader {
+    type Asset = SceneFile;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<SceneFile, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(SceneFile { bytes })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scn.ron", BINARY_SCENE_EXTENSION]
+    }
+}
+
+/// The asset path of a file in [`SCENE_SOURCE`]. Relative paths are resolved against the working
+/// directory. Windows drive prefixes are dropped, so only the current drive is reachable.
+pub fn scene_asset_path(path: &Path) -> AssetPath<'static> {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let relative: PathBuf = absolute
+        .components()
+        .filter(|c| !matches!(c, Component::Prefix(_) | Component::RootDir))
+        .collect();
+    AssetPath::from_path(&relative)
+        .with_source(AssetSourceId::from(SCENE_SOURCE))
+        .into_owned()
+}
+
+/// A scene file requested through the [`AssetServer`] that hasn't been applied yet.
+pub struct PendingSceneLoad {
+    pub handle: Handle<SceneFile>,
+    /// The file being read; differs from [`SceneInfo::file_path`] when restoring a recovery file.
+    pub source: PathBuf,
+    /// Restored from [`LoadRequest::source`]: the scene stays dirty and keeps its path.
+    pub is_restore: bool,
+    /// Scene id of the selected entity, selected again after the load if it still exists.
+    pub selected: Option<SceneId>,
+}
+
+#[derive(Resource, Default)]
+pub struct SceneAssets {
+    pub pending: Option<PendingSceneLoad>,
+    /// Keeps the open scene's file loaded, so loading it again is a reload.
+    pub open: Option<Handle<SceneFile>>,
+}
+
+/// Starts loading the requested scene through the [`AssetServer`].
+pub fn load_system(
+    mut commands: Commands,
+    load_request: Option<Res<LoadRequest>>,
+    scene_info: Res<SceneInfo>,
+    editor_state: Res<EditorState>,
+    scene_entities: Query<(Entity, Option<&SceneId>), SceneEntityFilter>,
+    asset_server: Res<AssetServer>,
+    mut scene_assets: ResMut<SceneAssets>,
+) {
+    let Some(load_request) = load_request else {
+        return;
+    };
+    commands.remove_resource::<LoadRequest>();
+
+    let Some(source) = load_request.source.as_ref().or(scene_info.file_path.as_ref()).cloned() else {
+        error!("Cannot load: No file path set in SceneInfo");
+        return;
+    };
+
+    let asset_path = scene_asset_path(&source);
+    let handle = asset_server.load::<SceneFile>(asset_path.clone());
+    if asset_server.is_loaded(&handle) {
+        // Already in memory, so read the file again
+        asset_server.reload(asset_path);
+    }
+
+    // The id the selected entity was saved under, even if it hasn't been loaded with one yet
+    let selected = editor_state.selected_entity.and_then(|selected| {
+        scene_ids(scene_entities.iter().map(|(entity, id)| (entity, id.copied()))).get(&selected).copied()
+    });
+    scene_assets.pending = Some(PendingSceneLoad {
+        handle,
+        source,
+        is_restore: load_request.source.is_some(),
+        selected,
+    });
+}
+
+/// Replaces the scene with the pending scene file once the [`AssetServer`] has (re)loaded it.
+pub fn finish_scene_load(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<SceneFile>>,
+    mut scene_assets: ResMut<SceneAssets>,
+    asset_server: Res<AssetServer>,
+    files: Res<Assets<SceneFile>>,
+) {
+    let Some(pending) = &scene_assets.pending else {
+        events.clear();
+        return;
+    };
+    let ready = events
+        .read()
+        .any(|e| e.is_loaded_with_dependencies(&pending.handle) || e.is_modified(&pending.handle));
+    if asset_server.load_state(&pending.handle).is_failed() {
+        error!("Failed to load scene from {:?}", pending.source);
+        scene_assets.pending = None;
+        return;
+    }
+    if !ready {
+        return;
+    }
+
+    let Some(pending) = scene_assets.pending.take() else {
+        return;
+    };
+    let Some(file) = files.get(&pending.handle) else {
+        return;
+    };
+    commands.queue(ApplySceneFile {
+        bytes: file.bytes.clone(),
+        source: pending.source,
+        is_restore: pending.is_restore,
+        selected: pending.selected,
+    });
+    if !pending.is_restore {
+        scene_assets.open = Some(pending.handle);
+    }
+}
+
+/// Replaces the current scene with the contents of a scene file.
+pub struct ApplySceneFile {
+    pub bytes: Vec<u8>,
+    pub source: PathBuf,
+    pub is_restore: bool,
+    pub selected: Option<SceneId>,
+}
+
+impl Command for ApplySceneFile {
+    fn apply(self, world: &mut World) {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let mode = world.get_resource::<EditorConfig>().map(|c| c.scene_load_mode).unwrap_or_default();
+        let scene = match world.get_resource::<SceneMigrations>() {
+            Some(migrations) => parse_scene_file(&self.bytes, &self.source, &registry.read(), migrations, mode),
+            None => parse_scene_file(&self.bytes, &self.source, &registry.read(), &SceneMigrations::default(), mode),
+        };
+        let Some(scene) = scene else {
+            return;
+        };
+
+        // Clear current world
+        let mut roots = world.query_filtered::<Entity, (SceneEntityFilter, Without<ChildOf>)>();
+        let roots: Vec<Entity> = roots.iter(world).collect();
+        for entity in roots {
+            world.despawn(entity);
+        }
+
+        let mut entity_map = EntityHashMap::default();
+        if let Err(e) = scene.write_to_world(world, &mut entity_map) {
+            error!("Failed to spawn scene from {:?}: {}", self.source, e);
+            return;
+        }
+
+        let selected = self.selected.and_then(|id| {
+            entity_map
+                .values()
+                .copied()
+                .find(|&entity| world.get::<SceneId>(entity) == Some(&id))
+        });
+        if let Some(mut state) = world.get_resource_mut::<EditorState>() {
+            state.selected_entity = selected;
+        }
+
+        world.resource_mut::<SceneInfo>().is_dirty = self.is_restore;
+        if !self.is_restore {
+            world.resource_mut::<SceneInfo>().file_path = Some(self.source.clone());
+            if let Some(mut preferences) = world.get_resource_mut::<EditorPreferences>() {
+                preferences.add_recent(&self.source);
+            }
+            if let Some(mut watch) = world.get_resource_mut::<SceneWatch>() {
+                watch.mark_current(&self.source);
+            }
+            offer_recovery(&mut world.commands(), Some(&self.source));
+            world.flush();
+        }
+        info!("Scene loaded from {:?}", self.source);
+    }
+}
+
+/// Watches the open scene file for changes made outside the editor.
+#[derive(Resource, Debug)]
+pub struct SceneWatch {
+    pub enabled: bool,
+    /// How often the file's modification time is checked.
+    pub interval: Duration,
+    pub since_check: Duration,
+    /// The file and modification time the editor last read, wrote or was told about.
+    pub known: Option<(PathBuf, SystemTime)>,
+}
+
+impl Default for SceneWatch {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(1),
+            since_check: Duration::ZERO,
+            known: None,
+        }
+    }
+}
+
+impl SceneWatch {
+    /// Records the file's current modification time as seen by the editor.
+    pub fn mark_current(&mut self, path: &Path) {
+        self.known = modified_time(path).map(|time| (path.to_path_buf(), time));
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Offers to reload the open scene when its file changes on disk. Files the editor is still
+/// writing are skipped; the finished save marks them current.
+pub fn watch_scene_file(
+    mut commands: Commands,
+    mut watch: ResMut<SceneWatch>,
+    scene_info: Res<SceneInfo>,
+    save_tasks: Option<Res<SaveTasks>>,
+    time: Res<Time>,
+    dialogs: Query<(), With<ModalDialog>>,
+) {
+    if !watch.enabled {
+        return;
+    }
+    watch.since_check += time.delta();
+    if watch.since_check < watch.interval || !dialogs.is_empty() {
+        return;
+    }
+    watch.since_check = Duration::ZERO;
+
+    let Some(path) = &scene_info.file_path else {
+        return;
+    };
+    if save_tasks.is_some_and(|tasks| tasks.0.iter().any(|(saving, _)| saving == path)) {
+        return;
+    }
+    let Some(modified) = modified_time(path) else {
+        return;
+    };
+    match &watch.known {
+        Some((known_path, known_time)) if known_path == path => {
+            if modified <= *known_time {
+                return;
+            }
+        }
+        // A different file was opened or saved without going through the watch
+        _ => {
+            watch.known = Some((path.clone(), modified));
+            return;
+        }
+    }
+    watch.known = Some((path.clone(), modified));
+
+    let name = path.file_name().map_or_else(|| path.to_string_lossy(), |n| n.to_string_lossy());
+    let warning = if scene_info.is_dirty { " Your unsaved changes will be lost." } else { "" };
+    info!("Scene file {:?} changed on disk", path);
+    spawn_dialog(
+        &mut commands,
+        &format!("{} changed on disk. Reload it?{}", name, warning),
+        vec![
+            ("Reload".to_string(), DialogChoice::ReloadScene),
+            ("Keep Current".to_string(), DialogChoice::Cancel),
+        ],
+    );
+}
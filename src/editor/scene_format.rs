@@ -0,0 +1,1033 @@
+use bevy::prelude::*;
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::ecs::entity::EntityMapper;
+use bevy::reflect::serde::{SerializationData, TypedReflectDeserializer, TypedReflectSerializer};
+use bevy::reflect::{DynamicStruct, ReflectFromReflect, ReflectRef, StructInfo, TypeInfo, TypeRegistration, TypeRegistry};
+use ron::value::RawValue;
+use serde::de::{DeserializeSeed, MapAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Deserialize, Serialize, Serializer};
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use super::migration::SceneMigrations;
+
+/// Version written to the header of saved scenes by the editor itself. Migrations registered by
+/// games and plugins are versioned separately, in the `migrations` header.
+///
+/// Version 2 keys entities (and entity references) by [`SceneId`] instead of runtime entity bits.
+/// Ids start at 1, so each key is also valid entity bits. The `version` field is not part of Bevy's
+/// scene layout, so these files only load through [`deserialize_scene`], not Bevy's scene loader.
+pub const SCENE_FORMAT_VERSION: u32 = 2;
+
+const SCENE_ID_VERSION: u32 = 2;
+
+/// Extension of scene files saved in the binary format.
+pub const BINARY_SCENE_EXTENSION: &str = "scn.bin";
+
+// Start of every binary scene file, so loads can tell the formats apart by their contents.
+const BINARY_MAGIC: &[u8] = b"BSCN";
+
+/// How a scene file is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SceneEncoding {
+    /// Bevy's `.scn.ron` layout, readable and diffable.
+    #[default]
+    Ron,
+    /// The same layout with every value encoded by the reflect serializer in postcard. Smaller and
+    /// faster to load than RON.
+    ///
+    /// Values can't be migrated once encoded: type renames still apply, but a later
+    /// [`SceneMigrations`] step that renames a field or transforms a value of a type stored in the
+    /// file fails the load. Keep RON as the source format and convert to binary for shipping.
+    Binary,
+}
+
+impl SceneEncoding {
+    /// The encoding a save to `path` uses: binary for `.scn.bin` files, RON for anything else.
+    pub fn for_path(path: &Path) -> Self {
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        if name.ends_with(&format!(".{}", BINARY_SCENE_EXTENSION)) {
+            SceneEncoding::Binary
+        } else {
+            SceneEncoding::Ron
+        }
+    }
+
+    /// The encoding of a scene file's contents, whatever the file is called.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(BINARY_MAGIC) {
+            SceneEncoding::Binary
+        } else {
+            SceneEncoding::Ron
+        }
+    }
+}
+
+/// How unregistered component types are handled when a scene file is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SceneLoadMode {
+    /// Fail the whole load on the first type missing from the `AppTypeRegistry`.
+    Strict,
+    /// Skip unknown components, report them, and keep their raw RON so the next save writes them back.
+    /// Unknown resources are reported and dropped; the editor doesn't save resources.
+    #[default]
+    Lenient,
+}
+
+/// A component read from a scene file whose type is not registered in this build.
+#[derive(Reflect, Clone, Debug, Default, PartialEq)]
+pub struct UnknownComponent {
+    pub type_path: String,
+    pub ron: String,
+}
+
+/// Unknown components preserved verbatim for an entity during a lenient load.
+///
+/// The editor's scene serializer expands these back into regular component entries on save.
+/// Entity references inside them are not remapped.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct UnknownComponents(pub Vec<UnknownComponent>);
+
+/// Persistent id of an entity within its scene file.
+///
+/// Used as the entity key when saving, so resaving an unchanged scene produces the same file.
+/// Read from the file on load; entities without one are numbered by [`scene_ids`] when first saved
+/// and keep that id from then on. Ids start at 1.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[reflect(Component)]
+pub struct SceneId(pub u32);
+
+/// Settings for [`serialize_scene`].
+#[derive(Debug, Clone)]
+pub struct SceneSaveOptions {
+    pub version: u32,
+    /// Plugin migration versions, usually [`SceneMigrations::plugin_versions`]. Only written if
+    /// not empty.
+    pub migrations: BTreeMap<String, u32>,
+    /// Leave out struct fields equal to the type's `Default`. They are filled back in on load.
+    pub omit_default_fields: bool,
+}
+
+impl Default for SceneSaveOptions {
+    fn default() -> Self {
+        Self {
+            version: SCENE_FORMAT_VERSION,
+            migrations: BTreeMap::new(),
+            omit_default_fields: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneFormatError {
+    Parse(ron::error::SpannedError),
+    UnknownType(String),
+    Value { type_path: String, message: String },
+    Serialize(ron::Error),
+    Binary(String),
+}
+
+impl fmt::Display for SceneFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFormatError::Parse(e) => write!(f, "invalid scene file: {}", e),
+            SceneFormatError::UnknownType(type_path) => write!(f, "unregistered type `{}`", type_path),
+            SceneFormatError::Value { type_path, message } => write!(f, "invalid value for `{}`: {}", type_path, message),
+            SceneFormatError::Serialize(e) => write!(f, "failed to serialize scene: {}", e),
+            SceneFormatError::Binary(message) => write!(f, "invalid binary scene: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SceneFormatError {}
+
+/// Result of deserializing a scene file.
+pub struct LoadedScene {
+    pub scene: DynamicScene,
+    /// Unregistered component type paths, with the number of times each occurred. These are kept
+    /// as [`UnknownComponents`] where the encoding allows it.
+    pub unknown_types: BTreeMap<String, usize>,
+    /// Unregistered resource type paths. Their values are dropped, not preserved.
+    pub dropped_resources: Vec<String>,
+}
+
+/// Registry-independent view of a scene file. Values stay as raw RON until their type is resolved,
+/// which is what lets migrations rewrite them before reflection sees them.
+#[derive(Deserialize)]
+pub struct RawScene {
+    /// Files saved before the header existed are version 0.
+    #[serde(default)]
+    pub version: u32,
+    /// The version each plugin's migrations had reached when the file was saved.
+    #[serde(default)]
+    pub migrations: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub resources: BTreeMap<String, Box<RawValue>>,
+    pub entities: BTreeMap<u64, RawEntity>,
+}
+
+#[derive(Deserialize)]
+pub struct RawEntity {
+    pub components: BTreeMap<String, Box<RawValue>>,
+}
+
+/// The binary counterpart of [`RawScene`], written after a `BSCN` header.
+#[derive(Serialize, Deserialize)]
+pub struct BinaryScene {
+    pub version: u32,
+    pub migrations: BTreeMap<String, u32>,
+    pub resources: BTreeMap<String, BinaryValue>,
+    pub entities: BTreeMap<u64, BTreeMap<String, BinaryValue>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BinaryValue {
+    /// The value as written by the reflect serializer in postcard.
+    Reflect(Vec<u8>),
+    /// A preserved [`UnknownComponent`], still in RON because its type is unknown.
+    Ron(String),
+}
+
+/// Deserializes a `.scn.ron` file, migrating it to the current format version and then resolving
+/// every value against `registry`.
+///
+/// Every entity gets a [`SceneId`]: its key for current files, or its position for files saved
+/// before entities were keyed by id.
+///
+/// In [`SceneLoadMode::Lenient`] unknown components are attached to their entity as
+/// [`UnknownComponents`] and listed in `unknown_types`. Unknown resources are dropped and listed in
+/// `dropped_resources`.
+pub fn deserialize_scene(
+    input: &str,
+    registry: &TypeRegistry,
+    migrations: &SceneMigrations,
+    mode: SceneLoadMode,
+) -> Result<LoadedScene, SceneFormatError> {
+    let raw: RawScene = ron::from_str(input).map_err(SceneFormatError::Parse)?;
+    resolve_raw_scene(raw, registry, migrations, mode)
+}
+
+/// Deserializes a scene file in either encoding, telling them apart with [`SceneEncoding::detect`].
+///
+/// Binary files load like [`deserialize_scene`], except that unknown components can only be kept
+/// if they were unknown when the file was written as well; other unknown values are dropped.
+pub fn deserialize_scene_file(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+    migrations: &SceneMigrations,
+    mode: SceneLoadMode,
+) -> Result<LoadedScene, SceneFormatError> {
+    match SceneEncoding::detect(bytes) {
+        SceneEncoding::Ron => {
+            let raw: RawScene = ron::de::from_bytes(bytes).map_err(SceneFormatError::Parse)?;
+            resolve_raw_scene(raw, registry, migrations, mode)
+        }
+        SceneEncoding::Binary => {
+            let mut binary: BinaryScene = postcard::from_bytes(&bytes[BINARY_MAGIC.len()..])
+                .map_err(|e| SceneFormatError::Binary(e.to_string()))?;
+            let keyed_by_scene_id = binary.version >= SCENE_ID_VERSION;
+            migrations.migrate_binary(&mut binary)?;
+            build_scene(
+                &binary.resources,
+                binary.entities.iter().map(|(key, components)| (*key, components)),
+                keyed_by_scene_id,
+                mode,
+                |type_path, value| match value {
+                    BinaryValue::Reflect(bytes) => deserialize_binary_value(type_path, bytes, registry),
+                    BinaryValue::Ron(ron) => {
+                        let raw = RawValue::from_ron(ron).map_err(|e| SceneFormatError::Value {
+                            type_path: type_path.to_string(),
+                            message: e.to_string(),
+                        })?;
+                        deserialize_value(type_path, raw, registry)
+                    }
+                },
+                |value| match value {
+                    BinaryValue::Reflect(_) => None,
+                    BinaryValue::Ron(ron) => Some(ron.trim().to_string()),
+                },
+            )
+        }
+    }
+}
+
+fn resolve_raw_scene(
+    mut raw: RawScene,
+    registry: &TypeRegistry,
+    migrations: &SceneMigrations,
+    mode: SceneLoadMode,
+) -> Result<LoadedScene, SceneFormatError> {
+    let keyed_by_scene_id = raw.version >= SCENE_ID_VERSION;
+    migrations.migrate(&mut raw)?;
+    build_scene(
+        &raw.resources,
+        raw.entities.iter().map(|(key, entity)| (*key, &entity.components)),
+        keyed_by_scene_id,
+        mode,
+        |type_path, value| deserialize_value(type_path, value, registry),
+        |value| Some(value.trim().get_ron().to_string()),
+    )
+}
+
+// Resolves the values of a migrated scene. `preserve` returns the RON kept for an unknown component,
+// if it can be kept at all.
+fn build_scene<'a, V: 'a>(
+    resources: &BTreeMap<String, V>,
+    entities: impl Iterator<Item = (u64, &'a BTreeMap<String, V>)>,
+    keyed_by_scene_id: bool,
+    mode: SceneLoadMode,
+    mut deserialize: impl FnMut(&str, &V) -> Result<Option<Box<dyn PartialReflect>>, SceneFormatError>,
+    preserve: impl Fn(&V) -> Option<String>,
+) -> Result<LoadedScene, SceneFormatError> {
+    let check_mode = |type_path: &str| -> Result<(), SceneFormatError> {
+        if mode == SceneLoadMode::Strict {
+            return Err(SceneFormatError::UnknownType(type_path.to_string()));
+        }
+        Ok(())
+    };
+
+    let mut resources_out = Vec::new();
+    let mut dropped_resources = Vec::new();
+    for (type_path, value) in resources {
+        match deserialize(type_path, value)? {
+            Some(resource) => resources_out.push(resource),
+            None => {
+                check_mode(type_path)?;
+                dropped_resources.push(type_path.clone());
+            }
+        }
+    }
+
+    let mut unknown_types = BTreeMap::new();
+
+    let mut entities_out = Vec::new();
+    for (index, (key, raw_components)) in entities.enumerate() {
+        // Keys are entity bits in both layouts; references inside components use the same bits.
+        let entity = Entity::try_from_bits(key);
+        let scene_id = if keyed_by_scene_id {
+            u32::try_from(key).ok().filter(|id| *id > 0)
+        } else {
+            u32::try_from(index + 1).ok()
+        };
+        let (Some(entity), Some(scene_id)) = (entity, scene_id) else {
+            return Err(SceneFormatError::Value {
+                type_path: "bevy_ecs::entity::Entity".to_string(),
+                message: format!("invalid entity id {}", key),
+            });
+        };
+
+        let mut components = Vec::new();
+        let mut unknown = Vec::new();
+        for (type_path, value) in raw_components {
+            match deserialize(type_path, value)? {
+                Some(component) => components.push(component),
+                None => {
+                    check_mode(type_path)?;
+                    *unknown_types.entry(type_path.clone()).or_insert(0) += 1;
+                    if let Some(ron) = preserve(value) {
+                        unknown.push(UnknownComponent {
+                            type_path: type_path.clone(),
+                            ron,
+                        });
+                    }
+                }
+            }
+        }
+
+        if !unknown.is_empty() {
+            components.push(Box::new(UnknownComponents(unknown)).into_partial_reflect());
+        }
+        components.push(Box::new(SceneId(scene_id)).into_partial_reflect());
+
+        entities_out.push(bevy::scene::DynamicEntity { entity, components });
+    }
+
+    Ok(LoadedScene {
+        scene: DynamicScene { resources: resources_out, entities: entities_out },
+        unknown_types,
+        dropped_resources,
+    })
+}
+
+// Returns `Ok(None)` when the type is not registered.
+fn deserialize_binary_value(
+    type_path: &str,
+    bytes: &[u8],
+    registry: &TypeRegistry,
+) -> Result<Option<Box<dyn PartialReflect>>, SceneFormatError> {
+    let Some(registration) = registry.get_with_type_path(type_path) else {
+        return Ok(None);
+    };
+    let mut deserializer = postcard::Deserializer::from_bytes(bytes);
+    let value = TypedReflectDeserializer::new(registration, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| SceneFormatError::Value {
+            type_path: type_path.to_string(),
+            message: e.to_string(),
+        })?;
+    Ok(Some(concrete_value(registration, value)))
+}
+
+// Returns `Ok(None)` when the type is not registered.
+fn deserialize_value(
+    type_path: &str,
+    value: &RawValue,
+    registry: &TypeRegistry,
+) -> Result<Option<Box<dyn PartialReflect>>, SceneFormatError> {
+    let Some(registration) = registry.get_with_type_path(type_path) else {
+        return Ok(None);
+    };
+
+    let value_error = |message: String| SceneFormatError::Value {
+        type_path: type_path.to_string(),
+        message,
+    };
+
+    let ron = value.get_ron();
+    let mut deserializer = ron::Deserializer::from_str(ron).map_err(|e| value_error(e.to_string()))?;
+    let value = match TypedReflectDeserializer::new(registration, registry).deserialize(&mut deserializer) {
+        Ok(value) => value,
+        // Types deserialized through serde reject the missing fields of a value saved with
+        // `omit_default_fields`, so read the fields that are there one by one.
+        Err(e) => match (registration.type_info(), registration.data::<ReflectDefault>()) {
+            (TypeInfo::Struct(info), Some(_)) => {
+                let mut deserializer = ron::Deserializer::from_str(ron).map_err(|e| value_error(e.to_string()))?;
+                let fields = PartialStructSeed { info, registry }
+                    .deserialize(&mut deserializer)
+                    .map_err(|_| value_error(e.to_string()))?;
+                Box::new(fields)
+            }
+            _ => return Err(value_error(e.to_string())),
+        },
+    };
+
+    Ok(Some(concrete_value(registration, value)))
+}
+
+// Prefers concrete values, the same way Bevy's own scene deserializer does. Struct values missing
+// fields are applied over the type's `Default`.
+fn concrete_value(registration: &TypeRegistration, value: Box<dyn PartialReflect>) -> Box<dyn PartialReflect> {
+    if let Some(concrete) = registration
+        .data::<ReflectFromReflect>()
+        .and_then(|from_reflect| from_reflect.from_reflect(value.as_partial_reflect()))
+    {
+        return concrete.into_partial_reflect();
+    }
+    if let Some(reflect_default) = registration.data::<ReflectDefault>() {
+        let mut filled = reflect_default.default();
+        if filled.try_apply(value.as_partial_reflect()).is_ok() {
+            return filled.into_partial_reflect();
+        }
+    }
+    value
+}
+
+// Reads a struct value field by field, like `TypedReflectDeserializer` does for reflected structs,
+// so fields can be missing even if the type deserializes through serde.
+struct PartialStructSeed<'a> {
+    info: &'static StructInfo,
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for PartialStructSeed<'_> {
+    type Value = DynamicStruct;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let name = self.info.type_path_table().ident().unwrap_or_default();
+        deserializer.deserialize_struct(name, self.info.field_names(), self)
+    }
+}
+
+impl<'de> Visitor<'de> for PartialStructSeed<'_> {
+    type Value = DynamicStruct;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "struct `{}`", self.info.type_path())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = DynamicStruct::default();
+        while let Some(name) = map.next_key::<String>()? {
+            let field = self
+                .info
+                .field(&name)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown field `{}`", name)))?;
+            let registration = self
+                .registry
+                .get(field.type_id())
+                .ok_or_else(|| serde::de::Error::custom(format!("unregistered type `{}`", field.type_path())))?;
+            let value = map.next_value_seed(TypedReflectDeserializer::new(registration, self.registry))?;
+            fields.insert_boxed(field.name(), value);
+        }
+        Ok(fields)
+    }
+}
+
+/// The [`SceneId`] component of a scene entity, if it has one.
+pub fn scene_id(entity: &bevy::scene::DynamicEntity) -> Option<SceneId> {
+    entity
+        .components
+        .iter()
+        .find(|c| c.get_represented_type_info().is_some_and(|info| info.type_id() == TypeId::of::<SceneId>()))
+        .and_then(|c| SceneId::from_reflect(c.as_ref()))
+}
+
+/// Removes the [`SceneId`]s read from a file, so the entities are numbered afresh when they are
+/// saved as part of another scene.
+pub fn clear_scene_ids(scene: &mut DynamicScene) {
+    for entity in &mut scene.entities {
+        entity.components.retain(|component| {
+            component
+                .get_represented_type_info()
+                .is_none_or(|info| info.type_id() != TypeId::of::<SceneId>())
+        });
+    }
+}
+
+/// Replaces the [`SceneId`] of every entity in `scene` with its id in `ids`.
+pub fn set_scene_ids(scene: &mut DynamicScene, ids: &HashMap<Entity, SceneId>) {
+    clear_scene_ids(scene);
+    for entity in &mut scene.entities {
+        if let Some(id) = ids.get(&entity.entity) {
+            entity.components.push(Box::new(*id).into_partial_reflect());
+        }
+    }
+}
+
+/// The ids entities are saved under: their own [`SceneId`] where it is set and unique, otherwise
+/// the next id after the highest one in use, in spawn order. Saves insert the new ids on their
+/// entities so later saves reuse them.
+pub fn scene_ids(entities: impl IntoIterator<Item = (Entity, Option<SceneId>)>) -> HashMap<Entity, SceneId> {
+    // Spawn order, not `Entity`'s ordering, which compares generations first.
+    let mut sorted: Vec<_> = entities.into_iter().collect();
+    sorted.sort_by_key(|(entity, _)| entity.index());
+
+    let mut used = HashSet::new();
+    let mut ids = HashMap::default();
+    let mut missing = Vec::new();
+    for (entity, id) in sorted {
+        match id {
+            Some(id) if id.0 > 0 && used.insert(id.0) => {
+                ids.insert(entity, id);
+            }
+            _ => missing.push(entity),
+        }
+    }
+
+    let first = used.iter().max().map_or(1, |max| max + 1);
+    ids.extend(missing.into_iter().zip((first..).map(SceneId)));
+    ids
+}
+
+/// Serializes a scene in Bevy's `.scn.ron` layout behind a `version` header, writing
+/// [`UnknownComponents`] back as the original component entries.
+///
+/// Entities are keyed and ordered by [`SceneId`] (entities without one are numbered after the
+/// highest id) and components are ordered by type path, so the output is deterministic.
+pub fn serialize_scene(
+    scene: &DynamicScene,
+    registry: &TypeRegistry,
+    options: &SceneSaveOptions,
+) -> Result<String, SceneFormatError> {
+    let (entities, ids) = keyed_entities(scene);
+    let context = SerializeContext {
+        registry,
+        ids,
+        omit_default_fields: options.omit_default_fields,
+    };
+
+    let pretty_config = ron::ser::PrettyConfig::default()
+        .indentor("  ".to_string())
+        .new_line("\n".to_string());
+
+    let document = SceneDocument {
+        version: options.version,
+        migrations: &options.migrations,
+        resources: &scene.resources,
+        entities,
+        context: &context,
+    };
+    ron::ser::to_string_pretty(&document, pretty_config).map_err(SceneFormatError::Serialize)
+}
+
+/// Serializes a scene in the binary format, with the same keys and ordering as [`serialize_scene`].
+///
+/// Fields are always written in full, since binary values can't leave any out.
+pub fn serialize_scene_binary(
+    scene: &DynamicScene,
+    registry: &TypeRegistry,
+    options: &SceneSaveOptions,
+) -> Result<Vec<u8>, SceneFormatError> {
+    let (entities, ids) = keyed_entities(scene);
+    let context = SerializeContext {
+        registry,
+        ids,
+        omit_default_fields: false,
+    };
+
+    let encode = |values: &[Box<dyn PartialReflect>]| -> Result<BTreeMap<String, BinaryValue>, SceneFormatError> {
+        let entries = EntryMap::new(values, &context).map_err(SceneFormatError::Binary)?;
+        entries
+            .entries
+            .into_iter()
+            .map(|(type_path, value)| {
+                let value = match value {
+                    EntryValue::Reflect(value) => encode_binary_value(value, registry),
+                    EntryValue::Mapped(value) => encode_binary_value(value.as_partial_reflect(), registry),
+                    EntryValue::Raw(raw) => Ok(BinaryValue::Ron(raw.get_ron().to_string())),
+                };
+                value.map(|value| (type_path.into_owned(), value))
+            })
+            .collect()
+    };
+
+    let document = BinaryScene {
+        version: options.version,
+        migrations: options.migrations.clone(),
+        resources: encode(&scene.resources)?,
+        entities: entities
+            .into_iter()
+            .map(|(id, entity)| Ok((u64::from(id), encode(&entity.components)?)))
+            .collect::<Result<_, SceneFormatError>>()?,
+    };
+    let mut bytes = BINARY_MAGIC.to_vec();
+    bytes.extend(postcard::to_allocvec(&document).map_err(|e| SceneFormatError::Binary(e.to_string()))?);
+    Ok(bytes)
+}
+
+/// Serializes a scene with the given encoding; RON is returned as its UTF-8 bytes.
+pub fn serialize_scene_as(
+    scene: &DynamicScene,
+    registry: &TypeRegistry,
+    options: &SceneSaveOptions,
+    encoding: SceneEncoding,
+) -> Result<Vec<u8>, SceneFormatError> {
+    match encoding {
+        SceneEncoding::Ron => serialize_scene(scene, registry, options).map(String::into_bytes),
+        SceneEncoding::Binary => serialize_scene_binary(scene, registry, options),
+    }
+}
+
+fn encode_binary_value(value: &dyn PartialReflect, registry: &TypeRegistry) -> Result<BinaryValue, SceneFormatError> {
+    postcard::to_allocvec(&TypedReflectSerializer::new(value, registry))
+        .map(BinaryValue::Reflect)
+        .map_err(|e| SceneFormatError::Value {
+            type_path: value.reflect_type_path().to_string(),
+            message: e.to_string(),
+        })
+}
+
+// The entities of `scene` keyed and ordered by their [`scene_ids`].
+fn keyed_entities(scene: &DynamicScene) -> (Vec<(u32, &bevy::scene::DynamicEntity)>, HashMap<Entity, u32>) {
+    let ids: HashMap<Entity, u32> = scene_ids(scene.entities.iter().map(|entity| (entity.entity, scene_id(entity))))
+        .into_iter()
+        .map(|(entity, id)| (entity, id.0))
+        .collect();
+    let mut entities: Vec<_> = scene.entities.iter().map(|entity| (ids[&entity.entity], entity)).collect();
+    entities.sort_by_key(|(id, _)| *id);
+    (entities, ids)
+}
+
+struct SerializeContext<'a> {
+    registry: &'a TypeRegistry,
+    ids: HashMap<Entity, u32>,
+    omit_default_fields: bool,
+}
+
+enum EntryValue<'a> {
+    Reflect(&'a dyn PartialReflect),
+    Mapped(Box<dyn Reflect>),
+    Raw(Box<RawValue>),
+}
+
+// Maps the entities in a scene to the entities whose bits are their scene ids.
+struct SceneIdMapper<'a>(&'a HashMap<Entity, u32>);
+
+impl EntityMapper for SceneIdMapper<'_> {
+    fn get_mapped(&mut self, source: Entity) -> Entity {
+        // References to entities outside the scene keep their runtime bits, as Bevy writes them.
+        self.0
+            .get(&source)
+            .and_then(|id| Entity::try_from_bits(u64::from(*id)))
+            .unwrap_or(source)
+    }
+
+    fn set_mapped(&mut self, _source: Entity, _target: Entity) {}
+}
+
+// Rewrites the entity references of a component to scene ids through its `MapEntities`
+// implementation, which also covers types like `ChildOf` that serialize through serde.
+fn map_to_scene_ids(value: &dyn PartialReflect, context: &SerializeContext) -> Option<Box<dyn Reflect>> {
+    let registration = context.registry.get(value.get_represented_type_info()?.type_id())?;
+    let reflect_component = registration.data::<ReflectComponent>()?;
+    let mut mapped = value.reflect_clone().ok()?;
+    reflect_component.map_entities(mapped.as_mut(), &mut SceneIdMapper(&context.ids));
+    Some(mapped)
+}
+
+struct SceneDocument<'a> {
+    version: u32,
+    migrations: &'a BTreeMap<String, u32>,
+    resources: &'a [Box<dyn PartialReflect>],
+    entities: Vec<(u32, &'a bevy::scene::DynamicEntity)>,
+    context: &'a SerializeContext<'a>,
+}
+
+impl Serialize for SceneDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Scene", 4)?;
+        state.serialize_field("version", &self.version)?;
+        if self.migrations.is_empty() {
+            state.skip_field("migrations")?;
+        } else {
+            state.serialize_field("migrations", self.migrations)?;
+        }
+        state.serialize_field("resources", &EntryMap::new(self.resources, self.context).map_err(serde::ser::Error::custom)?)?;
+        state.serialize_field("entities", &EntitiesDocument { entities: &self.entities, context: self.context })?;
+        state.end()
+    }
+}
+
+struct EntitiesDocument<'a> {
+    entities: &'a [(u32, &'a bevy::scene::DynamicEntity)],
+    context: &'a SerializeContext<'a>,
+}
+
+impl Serialize for EntitiesDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_map(Some(self.entities.len()))?;
+        for (id, entity) in self.entities {
+            let components = EntryMap::new(&entity.components, self.context).map_err(serde::ser::Error::custom)?;
+            state.serialize_entry(id, &EntityDocument { components })?;
+        }
+        state.end()
+    }
+}
+
+struct EntityDocument<'a> {
+    components: EntryMap<'a>,
+}
+
+impl Serialize for EntityDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Entity", 1)?;
+        state.serialize_field("components", &self.components)?;
+        state.end()
+    }
+}
+
+// Type path -> value, sorted by type path like Bevy's `SceneMapSerializer`.
+struct EntryMap<'a> {
+    entries: Vec<(Cow<'a, str>, EntryValue<'a>)>,
+    context: &'a SerializeContext<'a>,
+}
+
+impl<'a> EntryMap<'a> {
+    fn new(values: &'a [Box<dyn PartialReflect>], context: &'a SerializeContext<'a>) -> Result<Self, String> {
+        let mut entries = Vec::with_capacity(values.len());
+        for value in values {
+            let Some(type_info) = value.get_represented_type_info() else {
+                return Err(format!("`{}` does not represent a registered type", value.reflect_type_path()));
+            };
+
+            // The scene id is the entity key, not a component entry.
+            if type_info.type_id() == TypeId::of::<SceneId>() {
+                continue;
+            }
+
+            if type_info.type_id() == TypeId::of::<UnknownComponents>() {
+                let Some(unknown) = UnknownComponents::from_reflect(value.as_ref()) else {
+                    return Err("malformed UnknownComponents value".to_string());
+                };
+                for component in unknown.0 {
+                    let raw = RawValue::from_boxed_ron(component.ron.into_boxed_str())
+                        .map_err(|e| format!("preserved value for `{}` is invalid: {}", component.type_path, e))?;
+                    entries.push((Cow::Owned(component.type_path), EntryValue::Raw(raw)));
+                }
+                continue;
+            }
+
+            let value = match map_to_scene_ids(value.as_ref(), context) {
+                Some(mapped) => EntryValue::Mapped(mapped),
+                None => EntryValue::Reflect(value.as_ref()),
+            };
+            entries.push((Cow::Borrowed(type_info.type_path()), value));
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(Self { entries, context })
+    }
+}
+
+impl Serialize for EntryMap<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_map(Some(self.entries.len()))?;
+        for (type_path, value) in &self.entries {
+            let value = match value {
+                EntryValue::Reflect(value) => *value,
+                EntryValue::Mapped(value) => value.as_partial_reflect(),
+                EntryValue::Raw(raw) => {
+                    state.serialize_entry(type_path, raw.as_ref())?;
+                    continue;
+                }
+            };
+            match self.context.omit_default_fields.then(|| WithoutDefaultFields::new(value, self.context.registry)).flatten() {
+                Some(compact) => state.serialize_entry(type_path, &compact)?,
+                None => state.serialize_entry(type_path, &TypedReflectSerializer::new(value, self.context.registry))?,
+            }
+        }
+        state.end()
+    }
+}
+
+// Serializes a struct value without the fields that equal the same field of its `Default`.
+struct WithoutDefaultFields<'a> {
+    info: &'static StructInfo,
+    fields: Vec<(&'static str, &'a dyn PartialReflect)>,
+    registry: &'a TypeRegistry,
+}
+
+impl<'a> WithoutDefaultFields<'a> {
+    // Returns `None` when nothing can be left out.
+    fn new(value: &'a dyn PartialReflect, registry: &'a TypeRegistry) -> Option<Self> {
+        let (ReflectRef::Struct(value), Some(TypeInfo::Struct(info))) = (value.reflect_ref(), value.get_represented_type_info())
+        else {
+            return None;
+        };
+        let registration = registry.get(info.type_id())?;
+        let default = registration.data::<ReflectDefault>()?.default();
+        let ReflectRef::Struct(default) = default.reflect_ref() else {
+            return None;
+        };
+        let skipped = registration.data::<SerializationData>();
+
+        let mut omitted = false;
+        let mut fields = Vec::with_capacity(info.field_len());
+        for (index, field) in info.iter().enumerate() {
+            if skipped.is_some_and(|data| data.is_field_skipped(index)) {
+                continue;
+            }
+            let Some(field_value) = value.field(field.name()) else {
+                continue;
+            };
+            if default.field(field.name()).and_then(|d| field_value.reflect_partial_eq(d)) == Some(true) {
+                omitted = true;
+            } else {
+                fields.push((field.name(), field_value));
+            }
+        }
+        omitted.then_some(Self { info, fields, registry })
+    }
+}
+
+impl Serialize for WithoutDefaultFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = self.info.type_path_table().ident().unwrap_or_default();
+        let mut state = serializer.serialize_struct(name, self.fields.len())?;
+        for (field_name, value) in &self.fields {
+            state.serialize_field(field_name, &TypedReflectSerializer::new(*value, self.registry))?;
+        }
+        state.end()
+    }
+}
+
+/// A RON struct value split into its top-level `name: value` entries, keeping their original text
+/// (including whitespace and comments) so it can be rebuilt with entries added or removed.
+pub struct StructFields<'a> {
+    /// Everything up to and including the opening `(`.
+    pub prefix: &'a str,
+    pub entries: Vec<&'a str>,
+    /// Everything after the last entry, from its trailing comma (if any) to the end.
+    pub suffix: &'a str,
+}
+
+impl<'a> StructFields<'a> {
+    /// Returns `None` if `ron` is not a parenthesized struct.
+    pub fn parse(ron: &'a str) -> Option<Self> {
+        let bytes = ron.as_bytes();
+        let mut depth = 0usize;
+        let mut open = None;
+        let mut commas = Vec::new();
+        let mut close = None;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if let Some(end) = skip_raw_string(bytes, i) {
+                i = end;
+                continue;
+            }
+            match bytes[i] {
+                b'"' | b'\'' => {
+                    i = skip_quoted(bytes, i);
+                    continue;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'/') || bytes.get(i + 1) == Some(&b'*') => {
+                    i = skip_comment(bytes, i);
+                    continue;
+                }
+                b'(' | b'[' | b'{' => {
+                    if depth == 0 {
+                        if bytes[i] != b'(' || open.is_some() {
+                            return None;
+                        }
+                        open = Some(i);
+                    }
+                    depth += 1;
+                }
+                b')' | b']' | b'}' => {
+                    depth = depth.checked_sub(1)?;
+                    if depth == 0 {
+                        close = Some(i);
+                        break;
+                    }
+                }
+                b',' if depth == 1 => commas.push(i),
+                c if depth == 0 && !(c.is_ascii_alphanumeric() || c == b'_' || c.is_ascii_whitespace()) => return None,
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let open = open?;
+        let close = close?;
+        let mut boundaries = vec![open];
+        boundaries.extend(commas);
+
+        let mut entries: Vec<&str> = boundaries
+            .iter()
+            .zip(boundaries.iter().skip(1).copied().chain(std::iter::once(close)))
+            .map(|(&start, end)| &ron[start + 1..end])
+            .collect();
+
+        // A trailing comma leaves an empty last segment; it belongs to the suffix.
+        let mut suffix_start = close;
+        if entries.last().is_some_and(|e| Self::field_name(e).is_none()) {
+            entries.pop();
+            suffix_start = *boundaries.last().unwrap();
+            if suffix_start == open {
+                suffix_start = close;
+            }
+        }
+
+        Some(Self {
+            prefix: &ron[..=open],
+            entries,
+            suffix: &ron[suffix_start..],
+        })
+    }
+
+    /// The field name at the start of an entry, skipping leading whitespace and comments.
+    pub fn field_name(entry: &str) -> Option<&str> {
+        Self::field_name_range(entry).map(|range| &entry[range])
+    }
+
+    /// Byte range of [`Self::field_name`] within `entry`.
+    pub fn field_name_range(entry: &str) -> Option<std::ops::Range<usize>> {
+        let bytes = entry.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i].is_ascii_whitespace() {
+                i += 1;
+            } else if bytes[i] == b'/' && matches!(bytes.get(i + 1), Some(b'/') | Some(b'*')) {
+                i = skip_comment(bytes, i);
+            } else {
+                break;
+            }
+        }
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+            i += 1;
+        }
+        let rest = entry[i..].trim_start();
+        (i > start && rest.starts_with(':')).then_some(start..i)
+    }
+
+    /// Reassembles the struct with `entries` in place of the original ones, keeping the struct
+    /// name and any comments around them.
+    pub fn rebuild(&self, entries: &[&str]) -> String {
+        // Without entries a trailing comma would be left on its own
+        let suffix = match entries {
+            [] => self.suffix.strip_prefix(',').unwrap_or(self.suffix),
+            _ => self.suffix,
+        };
+        let mut out = String::from(self.prefix);
+        out.push_str(&entries.join(","));
+        out.push_str(suffix);
+        out
+    }
+}
+
+// Returns the index just past the string or char literal starting at `start`.
+fn skip_quoted(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// Returns the index just past the raw string (`r"..."`, `r#"..."#`, `br"..."`) starting at
+// `start`, or `None` if there isn't one.
+fn skip_raw_string(bytes: &[u8], start: usize) -> Option<usize> {
+    let is_ident = |c: &u8| c.is_ascii_alphanumeric() || *c == b'_';
+    let mut i = start;
+    if bytes[i] == b'b' {
+        i += 1;
+    }
+    if bytes.get(i) != Some(&b'r') || start.checked_sub(1).and_then(|p| bytes.get(p)).is_some_and(is_ident) {
+        return None;
+    }
+    let hashes = bytes[i + 1..].iter().take_while(|&&c| c == b'#').count();
+    i += 1 + hashes;
+    if bytes.get(i) != Some(&b'"') {
+        return None;
+    }
+    let closing: Vec<u8> = std::iter::once(b'"').chain(std::iter::repeat_n(b'#', hashes)).collect();
+    Some(
+        bytes[i + 1..]
+            .windows(closing.len())
+            .position(|w| w == closing.as_slice())
+            .map_or(bytes.len(), |p| i + 1 + p + closing.len()),
+    )
+}
+
+// Returns the index just past the `//` or `/* */` comment starting at `start`.
+fn skip_comment(bytes: &[u8], start: usize) -> usize {
+    if bytes.get(start + 1) == Some(&b'/') {
+        return bytes[start..].iter().position(|&c| c == b'\n').map_or(bytes.len(), |p| start + p);
+    }
+    bytes[start + 2..]
+        .windows(2)
+        .position(|w| w == b"*/")
+        .map_or(bytes.len(), |p| start + 2 + p + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_struct_fields_rebuild_keeps_name_and_comments() {
+        let fields = StructFields::parse("Stats( /* fast */ speed: 1.0, // done\n)").unwrap();
+        assert_eq!(fields.entries.len(), 1);
+        assert_eq!(fields.rebuild(&fields.entries), "Stats( /* fast */ speed: 1.0, // done\n)");
+        assert_eq!(fields.rebuild(&[]), "Stats( // done\n)");
+        assert_eq!(StructFields::parse("Stats()").unwrap().rebuild(&[]), "Stats()");
+    }
+
+    #[test]
+    fn test_struct_fields_skip_raw_strings() {
+        let fields = StructFields::parse(r##"(label: r#"a", hp: (1"#, hp: 2, bytes: br"x)")"##).unwrap();
+        let names: Vec<_> = fields.entries.iter().filter_map(|entry| StructFields::field_name(entry)).collect();
+        assert_eq!(names, ["label", "hp", "bytes"]);
+    }
+}
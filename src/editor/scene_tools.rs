@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::EntityHashMap;
+use std::any::TypeId;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use super::menu::{encode_world_scene, write_atomic};
+use super::migration::SceneMigrations;
+use super::resources::EditorConfig;
+use super::save_filter::{self, SaveFilter, SceneEntityFilter};
+use super::scene_format::{self, LoadedScene, SceneEncoding, SceneId, SceneLoadMode, UnknownComponents};
+
+const USAGE: &str = "Usage: bevy-editor-cli <command> [arguments]
+
+Commands:
+  validate <scene>...        Check that scenes load against the type registry
+  convert <input> <output>   Convert a scene; the output extension picks RON or binary (.scn.bin)
+  normalize <scene> [output] Write a scene as canonical RON, to stdout without an output
+  list <scene>               List the entities of a scene and their components";
+
+#[derive(Debug)]
+pub enum SceneToolError {
+    Io(PathBuf, std::io::Error),
+    Format(PathBuf, Box<scene_format::SceneFormatError>),
+    /// The scene uses types missing from the registry.
+    UnknownTypes(PathBuf, Vec<String>),
+    Spawn(PathBuf, String),
+    Serialize(PathBuf),
+}
+
+impl fmt::Display for SceneToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneToolError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            SceneToolError::Format(path, e) => write!(f, "{}: {}", path.display(), e),
+            SceneToolError::UnknownTypes(path, types) => {
+                write!(f, "{}: unregistered types {}", path.display(), types.join(", "))
+            }
+            SceneToolError::Spawn(path, e) => write!(f, "{}: failed to spawn: {}", path.display(), e),
+            SceneToolError::Serialize(path) => write!(f, "{}: failed to serialize", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SceneToolError {}
+
+/// A windowless app set up like the editor for reading and writing scene files.
+///
+/// Only reflected types compiled into the binary are known, so games with their own components
+/// should build their own tool from this app and [`run`].
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<EditorConfig>()
+        .init_resource::<SceneMigrations>()
+        .insert_resource(SaveFilter {
+            denied: save_filter::runtime_components().into_iter().collect(),
+        })
+        .register_type::<UnknownComponents>()
+        .register_type::<SceneId>()
+        .register_type::<save_filter::EditorOnly>()
+        .register_type::<super::prefab::PrefabLink>()
+        .register_type::<super::primitives::PrimitiveMesh>();
+    app
+}
+
+/// Reads a scene file in either format, keeping unknown components like a lenient editor load.
+pub fn load_scene(world: &World, path: &Path) -> Result<LoadedScene, SceneToolError> {
+    let bytes = std::fs::read(path).map_err(|e| SceneToolError::Io(path.to_path_buf(), e))?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let migrations = world.resource::<SceneMigrations>();
+    scene_format::deserialize_scene_file(&bytes, &registry, migrations, SceneLoadMode::Lenient)
+        .map_err(|e| SceneToolError::Format(path.to_path_buf(), Box::new(e)))
+}
+
+/// Loads a scene and spawns it into `world`, failing on any type the registry doesn't know.
+/// Returns the number of entities.
+pub fn validate_scene(world: &mut World, path: &Path) -> Result<usize, SceneToolError> {
+    let loaded = load_scene(world, path)?;
+    if !loaded.unknown_types.is_empty() || !loaded.dropped_resources.is_empty() {
+        let types = loaded.unknown_types.into_keys().chain(loaded.dropped_resources).collect();
+        return Err(SceneToolError::UnknownTypes(path.to_path_buf(), types));
+    }
+
+    let mut entity_map = EntityHashMap::default();
+    let result = loaded.scene.write_to_world(world, &mut entity_map);
+    for entity in entity_map.values() {
+        if let Ok(entity) = world.get_entity_mut(*entity) {
+            entity.despawn();
+        }
+    }
+    result.map_err(|e| SceneToolError::Spawn(path.to_path_buf(), e.to_string()))?;
+    Ok(loaded.scene.entities.len())
+}
+
+/// Replaces the scene in `world` with the file at `path` and encodes it the way the editor saves,
+/// returning the bytes and the unknown component types that were carried over. Unknown resources
+/// are dropped with a warning.
+pub fn resave_scene(
+    world: &mut World,
+    path: &Path,
+    encoding: SceneEncoding,
+) -> Result<(Vec<u8>, Vec<String>), SceneToolError> {
+    let loaded = load_scene(world, path)?;
+    if !loaded.dropped_resources.is_empty() {
+        eprintln!("{}: dropped unregistered resources: {}", path.display(), loaded.dropped_resources.join(", "));
+    }
+
+    let mut roots = world.query_filtered::<Entity, (SceneEntityFilter, Without<ChildOf>)>();
+    let roots: Vec<Entity> = roots.iter(world).collect();
+    for entity in roots {
+        world.despawn(entity);
+    }
+    loaded
+        .scene
+        .write_to_world(world, &mut EntityHashMap::default())
+        .map_err(|e| SceneToolError::Spawn(path.to_path_buf(), e.to_string()))?;
+
+    let bytes = encode_world_scene(world, encoding).ok_or_else(|| SceneToolError::Serialize(path.to_path_buf()))?;
+    Ok((bytes, loaded.unknown_types.into_keys().collect()))
+}
+
+/// An entity of a scene file as shown by `list`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySummary {
+    pub id: Option<u32>,
+    pub name: Option<String>,
+    /// Short type paths, sorted. Unknown components are marked with `?`.
+    pub components: Vec<String>,
+}
+
+pub fn list_scene(world: &World, path: &Path) -> Result<Vec<EntitySummary>, SceneToolError> {
+    let loaded = load_scene(world, path)?;
+    let summaries = loaded
+        .scene
+        .entities
+        .iter()
+        .map(|entity| {
+            let mut name = None;
+            let mut components = Vec::new();
+            for component in &entity.components {
+                let Some(info) = component.get_represented_type_info() else {
+                    components.push(component.reflect_type_path().to_string());
+                    continue;
+                };
+                if info.type_id() == TypeId::of::<SceneId>() {
+                    continue;
+                }
+                if info.type_id() == TypeId::of::<UnknownComponents>() {
+                    let unknown = UnknownComponents::from_reflect(component.as_ref()).unwrap_or_default();
+                    components.extend(unknown.0.into_iter().map(|c| format!("{}?", c.type_path)));
+                    continue;
+                }
+                if info.type_id() == TypeId::of::<Name>() {
+                    name = Name::from_reflect(component.as_ref()).map(|n| n.to_string());
+                }
+                components.push(info.type_path_table().short_path().to_string());
+            }
+            components.sort();
+            EntitySummary {
+                id: scene_format::scene_id(entity).map(|id| id.0),
+                name,
+                components,
+            }
+        })
+        .collect();
+    Ok(summaries)
+}
+
+/// Runs a `bevy-editor-cli` command line (without the program name) against `world`.
+pub fn run(world: &mut World, args: &[String]) -> ExitCode {
+    let paths: Vec<&Path> = args.iter().skip(1).map(Path::new).collect();
+    let result = match (args.first().map(String::as_str), paths.as_slice()) {
+        (Some("validate"), [_, ..]) => {
+            let mut failed = false;
+            for path in &paths {
+                match validate_scene(world, path) {
+                    Ok(count) => println!("{}: ok ({} entities)", path.display(), count),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        failed = true;
+                    }
+                }
+            }
+            return if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS };
+        }
+        (Some("convert"), [input, output]) => resave_scene(world, input, SceneEncoding::for_path(output))
+            .and_then(|(bytes, unknown)| {
+                warn_unknown(input, &unknown);
+                write_atomic(output, &bytes).map_err(|e| SceneToolError::Io(output.to_path_buf(), e))
+            }),
+        (Some("normalize"), [input, rest @ ..]) if rest.len() <= 1 => {
+            resave_scene(world, input, SceneEncoding::Ron).and_then(|(bytes, unknown)| {
+                warn_unknown(input, &unknown);
+                match rest.first() {
+                    Some(output) => write_atomic(output, &bytes).map_err(|e| SceneToolError::Io(output.to_path_buf(), e)),
+                    None => std::io::stdout()
+                        .write_all(&bytes)
+                        .map_err(|e| SceneToolError::Io(PathBuf::from("<stdout>"), e)),
+                }
+            })
+        }
+        (Some("list"), [input]) => list_scene(world, input).map(|entities| {
+            for entity in entities {
+                let id = entity.id.map_or("-".to_string(), |id| id.to_string());
+                let name = entity.name.map(|n| format!(" \"{}\"", n)).unwrap_or_default();
+                println!("{}{}: {}", id, name, entity.components.join(", "));
+            }
+        }),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn warn_unknown(path: &Path, types: &[String]) {
+    if !types.is_empty() {
+        eprintln!("{}: kept unregistered types as-is: {}", path.display(), types.join(", "));
+    }
+}
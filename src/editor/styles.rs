@@ -10,6 +10,8 @@ pub const BUTTON_COLOR_PRESSED: Color = Color::srgb(0.45, 0.45, 0.45);
 pub const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
 pub const RESIZE_HANDLE_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
 pub const SELECTED_COLOR: Color = Color::srgb(0.2, 0.4, 0.6);
+pub const TEXT_SELECTION_COLOR: Color = Color::srgb(0.25, 0.45, 0.75);
+pub const CARET_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
 
 pub fn root_node_style() -> Node {
     Node {
@@ -67,6 +69,15 @@ pub fn text_style(asset_server: &AssetServer) -> TextFont {
     }
 }
 
+/// Inspector field labels for prefab overrides.
+pub fn override_text_style(asset_server: &AssetServer) -> TextFont {
+    TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 12.0,
+        ..default()
+    }
+}
+
 pub fn menu_bar_style() -> Node {
     Node {
         width: Val::Percent(100.0),
@@ -100,6 +111,18 @@ pub fn dropdown_style() -> Node {
     }
 }
 
+pub fn submenu_style() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        top: Val::Px(0.0),
+        left: Val::Percent(100.0),
+        min_width: Val::Px(160.0),
+        flex_direction: FlexDirection::Column,
+        border: UiRect::all(Val::Px(1.0)),
+        ..default()
+    }
+}
+
 pub fn resize_handle_style() -> Node {
     Node {
         width: Val::Px(8.0),
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::Command;
+use std::path::{Path, PathBuf};
+use super::menu::{read_world_scene_file, SceneInfo};
+use super::primitives::{PrimitiveMesh, PrimitiveShape};
+use super::resources::EditorState;
+use super::save_filter::SceneEntityFilter;
+use super::scene_format;
+
+/// Folder searched for scene templates (`*.scn.ron`), relative to the working directory.
+pub const TEMPLATES_DIR: &str = "assets/templates";
+
+/// What a new scene starts with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneTemplate {
+    Empty,
+    /// A ground plane, a light and a camera.
+    Basic,
+    /// The contents of a scene file, which stays untouched.
+    File(PathBuf),
+}
+
+impl SceneTemplate {
+    pub fn label(&self) -> String {
+        match self {
+            SceneTemplate::Empty => "Empty".to_string(),
+            SceneTemplate::Basic => "Basic".to_string(),
+            SceneTemplate::File(path) => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map_or("Template", |n| n.trim_end_matches(".scn.ron"))
+                .to_string(),
+        }
+    }
+}
+
+/// The built-in templates followed by the `*.scn.ron` files in `dir`, sorted by name.
+pub fn discover_templates(dir: &Path) -> Vec<SceneTemplate> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.to_string_lossy().ends_with(".scn.ron"))
+        .collect();
+    files.sort();
+
+    [SceneTemplate::Empty, SceneTemplate::Basic]
+        .into_iter()
+        .chain(files.into_iter().map(SceneTemplate::File))
+        .collect()
+}
+
+/// Clears the scene and starts an untitled one from a template.
+pub struct NewSceneCommand {
+    pub template: SceneTemplate,
+}
+
+impl Command for NewSceneCommand {
+    fn apply(self, world: &mut World) {
+        let mut roots = world.query_filtered::<Entity, (SceneEntityFilter, Without<ChildOf>)>();
+        let roots: Vec<Entity> = roots.iter(world).collect();
+        for entity in roots {
+            world.despawn(entity);
+        }
+        if let Some(mut state) = world.get_resource_mut::<EditorState>() {
+            state.selected_entity = None;
+        }
+        world.insert_resource(SceneInfo::default());
+
+        match &self.template {
+            SceneTemplate::Empty => {}
+            SceneTemplate::Basic => spawn_basic_template(world),
+            SceneTemplate::File(path) => {
+                let registry = world.resource::<AppTypeRegistry>().clone();
+                let Some(mut scene) = read_world_scene_file(world, &registry.read(), path) else {
+                    return;
+                };
+                // The new scene assigns its own ids on the first save
+                scene_format::clear_scene_ids(&mut scene);
+                if let Err(e) = scene.write_to_world(world, &mut EntityHashMap::default()) {
+                    error!("Failed to spawn template {:?}: {}", path, e);
+                    return;
+                }
+            }
+        }
+        info!("New scene from template {}", self.template.label());
+    }
+}
+
+fn spawn_basic_template(world: &mut World) {
+    world.spawn((
+        Name::new("Ground"),
+        PrimitiveMesh {
+            shape: PrimitiveShape::Plane { size: Vec2::splat(10.0) },
+            color: Color::srgb(0.3, 0.5, 0.3),
+        },
+    ));
+    world.spawn((
+        Name::new("Light"),
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+    // Saved with the scene for the game to use; inactive so it doesn't cover the editor viewport
+    world.spawn((
+        Name::new("Camera"),
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            ..default()
+        },
+        Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
@@ -0,0 +1,363 @@
+use bevy::prelude::*;
+use std::ops::Range;
+use super::input::TextEdit;
+use super::resources::EditorState;
+use super::styles::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaretMotion {
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+/// An editable line of text. Focus it through [`EditorState::focused_input`]; the text is drawn
+/// by a [`text_field_text`] child.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct TextField {
+    pub text: String,
+    /// Byte offset of the caret, always on a char boundary.
+    pub caret: usize,
+    /// The other end of the selection, which runs between it and the caret.
+    pub anchor: Option<usize>,
+    /// The last set or committed text, restored by [`TextEdit::Cancel`].
+    pub original: String,
+}
+
+impl TextField {
+    /// A field with the caret after `text`.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self { caret: text.len(), original: text.clone(), text, anchor: None }
+    }
+
+    /// Replaces the text and the original, moving the caret to the end and dropping the selection.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        *self = Self::new(text);
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&a| a != self.caret)?;
+        Some(anchor.min(self.caret)..anchor.max(self.caret))
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|range| &self.text[range])
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        let Some(range) = selection else {
+            return false;
+        };
+        self.caret = range.start;
+        self.text.replace_range(range, "");
+        true
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.caret].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.caret + self.text[self.caret..].chars().next().map_or(0, char::len_utf8)
+    }
+
+    /// Inserts at the caret, replacing the selection.
+    pub fn insert(&mut self, inserted: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.caret, inserted);
+        self.caret += inserted.len();
+    }
+
+    /// Deletes the selection or the character before the caret. Returns whether the text changed.
+    pub fn backspace(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        if self.caret == 0 {
+            return false;
+        }
+        let start = self.previous_boundary();
+        self.text.replace_range(start..self.caret, "");
+        self.caret = start;
+        true
+    }
+
+    /// Deletes the selection or the character after the caret. Returns whether the text changed.
+    pub fn delete(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        if self.caret == self.text.len() {
+            return false;
+        }
+        let end = self.next_boundary();
+        self.text.replace_range(self.caret..end, "");
+        true
+    }
+
+    /// Moves the caret, extending the selection if `select` is set. Left and Right without
+    /// `select` collapse a selection to its start or end.
+    pub fn move_caret(&mut self, motion: CaretMotion, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else if let Some(range) = self.selection() {
+            self.anchor = None;
+            match motion {
+                CaretMotion::Left => {
+                    self.caret = range.start;
+                    return;
+                }
+                CaretMotion::Right => {
+                    self.caret = range.end;
+                    return;
+                }
+                CaretMotion::Home | CaretMotion::End => {}
+            }
+        } else {
+            self.anchor = None;
+        }
+        self.caret = match motion {
+            CaretMotion::Left => self.previous_boundary(),
+            CaretMotion::Right => self.next_boundary(),
+            CaretMotion::Home => 0,
+            CaretMotion::End => self.text.len(),
+        };
+    }
+
+    /// Puts back the original text. Returns whether the text changed.
+    pub fn revert(&mut self) -> bool {
+        let changed = self.text != self.original;
+        self.set_text(self.original.clone());
+        changed
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.text.len();
+    }
+
+    /// Applies one edit. Returns whether the text changed; submitting, cancelling and moving focus
+    /// are left to the caller.
+    pub fn apply(&mut self, edit: &TextEdit, clipboard: &mut dyn Clipboard) -> bool {
+        match edit {
+            TextEdit::Insert(inserted) => {
+                self.insert(inserted);
+                true
+            }
+            TextEdit::Backspace => self.backspace(),
+            TextEdit::Delete => self.delete(),
+            TextEdit::Move { motion, select } => {
+                self.move_caret(*motion, *select);
+                false
+            }
+            TextEdit::SelectAll => {
+                self.select_all();
+                false
+            }
+            TextEdit::Copy => {
+                if let Some(selected) = self.selected_text() {
+                    clipboard.set_text(selected.to_string());
+                }
+                false
+            }
+            TextEdit::Cut => {
+                if let Some(selected) = self.selected_text() {
+                    clipboard.set_text(selected.to_string());
+                }
+                self.delete_selection()
+            }
+            TextEdit::Paste => {
+                // Fields are a single line
+                let pasted: Option<String> = clipboard
+                    .get_text()
+                    .map(|text| text.chars().filter(|c| !c.is_control()).collect());
+                match pasted {
+                    Some(text) if !text.is_empty() => {
+                        self.insert(&text);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            TextEdit::Submit | TextEdit::Cancel | TextEdit::FocusNext { .. } => false,
+        }
+    }
+
+    /// The text before the selection, the selection, the caret and the text after it.
+    fn display_parts(&self, focused: bool) -> [&str; 4] {
+        match (focused, self.selection()) {
+            (false, _) => [&self.text, "", "", ""],
+            (true, Some(range)) => [&self.text[..range.start], &self.text[range.clone()], "", &self.text[range.end..]],
+            (true, None) => [&self.text[..self.caret], "", "|", &self.text[self.caret..]],
+        }
+    }
+}
+
+/// Where text fields cut and copy to and paste from.
+pub trait Clipboard: Send + Sync + 'static {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: String);
+}
+
+/// A clipboard private to the editor.
+#[derive(Default, Debug)]
+pub struct MemoryClipboard(pub Option<String>);
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.0.clone()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.0 = Some(text);
+    }
+}
+
+/// The system clipboard through `arboard`, falling back to a [`MemoryClipboard`] where there is
+/// none (e.g. headless).
+#[derive(Default)]
+pub struct SystemClipboard {
+    system: Option<arboard::Clipboard>,
+    unavailable: bool,
+    fallback: MemoryClipboard,
+}
+
+impl SystemClipboard {
+    fn system(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.system.is_none() && !self.unavailable {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.system = Some(clipboard),
+                Err(e) => {
+                    warn!("System clipboard unavailable, using the editor's own: {}", e);
+                    self.unavailable = true;
+                }
+            }
+        }
+        self.system.as_mut()
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        match self.system() {
+            Some(system) => system.get_text().ok(),
+            None => self.fallback.get_text(),
+        }
+    }
+
+    fn set_text(&mut self, text: String) {
+        match self.system() {
+            Some(system) => {
+                if let Err(e) = system.set_text(text) {
+                    warn!("Failed to copy to the clipboard: {}", e);
+                }
+            }
+            None => self.fallback.set_text(text),
+        }
+    }
+}
+
+/// The [`Clipboard`] used by the editor's text fields.
+#[derive(Resource)]
+pub struct EditorClipboard(pub Box<dyn Clipboard>);
+
+impl Default for EditorClipboard {
+    fn default() -> Self {
+        Self(Box::new(SystemClipboard::default()))
+    }
+}
+
+/// Marks the text entity that draws its parent [`TextField`].
+#[derive(Component)]
+pub struct TextFieldText;
+
+/// The child that draws a [`TextField`]: text before the selection or caret, the selection, the
+/// caret and the rest, each in its own span.
+pub fn text_field_text(font_size: f32) -> impl Bundle {
+    let font = TextFont { font_size, ..default() };
+    (
+        Text::default(),
+        font.clone(),
+        TextColor(TEXT_COLOR),
+        TextFieldText,
+        children![
+            (TextSpan::default(), font.clone(), TextColor(TEXT_COLOR)),
+            (TextSpan::default(), font.clone(), TextColor(TEXT_COLOR), TextBackgroundColor(TEXT_SELECTION_COLOR)),
+            (TextSpan::default(), font.clone(), TextColor(CARET_COLOR)),
+            (TextSpan::default(), font, TextColor(TEXT_COLOR)),
+        ],
+    )
+}
+
+pub fn render_text_fields(
+    editor_state: Res<EditorState>,
+    fields: Query<(Entity, Ref<TextField>, &Children)>,
+    displays: Query<&Children, With<TextFieldText>>,
+    mut spans: Query<&mut TextSpan>,
+) {
+    for (entity, field, children) in fields.iter() {
+        if !field.is_changed() && !editor_state.is_changed() {
+            continue;
+        }
+        let Some(parts) = children.iter().find_map(|child| displays.get(child).ok()) else {
+            continue;
+        };
+        let focused = editor_state.focused_input == Some(entity);
+        for (span, text) in parts.iter().zip(field.display_parts(focused)) {
+            if let Ok(mut span) = spans.get_mut(span)
+                && span.0 != text
+            {
+                span.0 = text.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_field_caret_selection_and_clipboard() {
+        let mut clipboard = MemoryClipboard::default();
+        let mut field = TextField::new("Vec3(1.0, 2.0)");
+        assert_eq!(field.caret, field.text.len());
+
+        // Edit in the middle instead of at the end
+        field.move_caret(CaretMotion::Home, false);
+        for _ in 0..5 {
+            field.move_caret(CaretMotion::Right, false);
+        }
+        assert!(field.delete());
+        assert!(field.apply(&TextEdit::Insert("5".to_string()), &mut clipboard));
+        assert!(field.apply(&TextEdit::Backspace, &mut clipboard));
+        assert!(field.apply(&TextEdit::Insert("3".to_string()), &mut clipboard));
+        assert_eq!(field.text, "Vec3(3.0, 2.0)");
+        assert_eq!(field.caret, 6);
+
+        // Shift-select to the end, copy, then paste over a select-all
+        field.move_caret(CaretMotion::End, true);
+        assert_eq!(field.selected_text(), Some(".0, 2.0)"));
+        assert!(!field.apply(&TextEdit::Copy, &mut clipboard));
+        assert_eq!(clipboard.0.as_deref(), Some(".0, 2.0)"));
+        field.move_caret(CaretMotion::Left, false);
+        assert_eq!((field.caret, field.selection()), (6, None), "Left collapses the selection to its start");
+        assert!(!field.apply(&TextEdit::SelectAll, &mut clipboard));
+        assert_eq!(field.selection(), Some(0..14));
+        assert!(field.apply(&TextEdit::Paste, &mut clipboard));
+        assert_eq!(field.text, ".0, 2.0)");
+
+        // Cut removes the selection; multi-byte characters move as one
+        field.set_text("扉x");
+        field.move_caret(CaretMotion::Left, false);
+        field.move_caret(CaretMotion::Left, true);
+        assert_eq!(field.selected_text(), Some("扉"));
+        assert!(field.apply(&TextEdit::Cut, &mut clipboard));
+        assert_eq!((field.text.as_str(), field.caret, clipboard.0.as_deref()), ("x", 0, Some("扉")));
+        assert!(!field.backspace());
+    }
+}
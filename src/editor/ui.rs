@@ -1,9 +1,12 @@
 use bevy::prelude::*;
+use bevy::ecs::system::Command;
 use super::components::*;
 use super::styles::*;
 use crate::editor::resources::EditorConfig;
 use bevy::input::mouse::AccumulatedMouseMotion;
+use crate::editor::keymap::{ShortcutsButton, ShortcutsPanel};
 use crate::editor::log::{LogPanel, LogPanelContent};
+use crate::editor::save_filter::EditorOnly;
 
 
 pub fn setup_editor_ui(mut commands: Commands) {
@@ -21,6 +24,7 @@ pub fn setup_editor_ui(mut commands: Commands) {
         },
           BackgroundColor(PANEL_COLOR),
           InspectorPanel,
+          EditorOnly,
           ResizablePanel::default(),
           GlobalTransform::default(),
           Transform::default(),
@@ -43,6 +47,7 @@ pub fn setup_editor_ui(mut commands: Commands) {
             ..default()
         },
         LogPanel,
+        EditorOnly,
         GlobalTransform::default(),
         Transform::default(),
         Visibility::default(),
@@ -79,6 +84,7 @@ pub fn setup_editor_ui(mut commands: Commands) {
              min_width: Val::Px(0.0),
              ..default()
         },
+        EditorOnly,
         GlobalTransform::default(),
         Transform::default(),
         Visibility::default(),
@@ -130,6 +136,7 @@ pub fn setup_editor_ui(mut commands: Commands) {
          },
          BackgroundColor(PANEL_COLOR),
          HierarchyPanel,
+         EditorOnly,
          ResizablePanel::default(),
          GlobalTransform::default(),
          Transform::default(),
@@ -202,6 +209,20 @@ pub fn setup_editor_ui(mut commands: Commands) {
                 Visibility::Hidden,
                 GlobalZIndex(10), // Ensure it's on top
             )).with_children(|dropdown| {
+                // New Scene
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::NewScene },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("New Scene..."),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
                 // Save
                 dropdown.spawn((
                     Button,
@@ -244,6 +265,82 @@ pub fn setup_editor_ui(mut commands: Commands) {
                     ));
                 });
 
+                // Recent Scenes, with its submenu to the right of the dropdown
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::RecentScenes },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Recent Scenes >"),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                    btn.spawn((
+                        submenu_style(),
+                        BackgroundColor(PANEL_COLOR),
+                        RecentScenesMenu,
+                        Visibility::Hidden,
+                    ));
+                });
+
+                // Import
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::Import },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Import..."),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+                // Import Under Parent...
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::ImportUnderParent },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Import Under Parent..."),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+                // Save as Prefab...
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::SavePrefab },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Save as Prefab..."),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+                // Instantiate Prefab...
+                dropdown.spawn((
+                    Button,
+                    menu_button_style(),
+                    BackgroundColor(BUTTON_COLOR_NORMAL),
+                    MenuButtonAction { action: MenuAction::InstantiatePrefab },
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new("Instantiate Prefab..."),
+                        TextFont::default(),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
                 // Exit
                 dropdown.spawn((
                     Button,
@@ -258,6 +355,51 @@ pub fn setup_editor_ui(mut commands: Commands) {
                     ));
                 });
             });
+
+            // Keyboard Shortcuts, listed in a panel below the button
+            menu.spawn((
+                Button,
+                menu_button_style(),
+                BackgroundColor(BUTTON_COLOR_NORMAL),
+                ShortcutsButton,
+            )).with_children(|btn| {
+                btn.spawn((
+                    Text::new("Shortcuts"),
+                    TextFont::default(),
+                    TextColor(TEXT_COLOR),
+                ));
+                btn.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(25.0),
+                        left: Val::Px(0.0),
+                        min_width: Val::Px(260.0),
+                        flex_direction: FlexDirection::Column,
+                        border: UiRect::all(Val::Px(1.0)),
+                        padding: UiRect::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    BackgroundColor(PANEL_COLOR),
+                    ShortcutsPanel,
+                    Visibility::Hidden,
+                    GlobalZIndex(10),
+                ));
+            });
+
+            // Save status, right-aligned
+            menu.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+                Node {
+                    margin: UiRect::left(Val::Auto),
+                    ..default()
+                },
+                SaveStatusText,
+            ));
         });
     });
 
@@ -270,6 +412,7 @@ pub fn setup_editor_ui(mut commands: Commands) {
              justify_content: JustifyContent::FlexStart,
              ..default()
         },
+        EditorOnly,
         GlobalTransform::default(),
         Transform::default(),
         Visibility::default(),
@@ -326,14 +469,26 @@ pub fn setup_editor_ui(mut commands: Commands) {
 }
 
 pub fn toggle_editor(
-    input: Res<ButtonInput<KeyCode>>,
-    mut config: ResMut<EditorConfig>,
-    mut query: Query<&mut Visibility, With<EditorRoot>>,
+    input: Res<super::input::EditorKeys>,
+    keymap: Res<super::keymap::EditorKeymap>,
+    mut commands: Commands,
 ) {
-    if input.just_pressed(KeyCode::F1) {
+    if keymap.just_pressed(super::keymap::EditorAction::ToggleEditor, &input.0) {
+        commands.queue(ToggleEditorCommand);
+    }
+}
+
+/// Shows or hides the whole editor UI.
+pub struct ToggleEditorCommand;
+
+impl Command for ToggleEditorCommand {
+    fn apply(self, world: &mut World) {
+        let mut config = world.resource_mut::<EditorConfig>();
         config.show_editor = !config.show_editor;
-        for mut vis in query.iter_mut() {
-            *vis = if config.show_editor { Visibility::Visible } else { Visibility::Hidden };
+        let visibility = if config.show_editor { Visibility::Visible } else { Visibility::Hidden };
+        let mut roots = world.query_filtered::<&mut Visibility, With<EditorRoot>>();
+        for mut vis in roots.iter_mut(world) {
+            *vis = visibility;
         }
     }
 }
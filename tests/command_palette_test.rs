@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::ecs::system::Command;
+    use bevy_editor_ai_test::editor::command_palette::{
+        command_palette_system, CommandPalette, CommandPaletteEntry, CommandPaletteInput, EditorCommands, OpenCommandPalette,
+    };
+    use bevy_editor_ai_test::editor::input::{TextEdit, TypedText};
+    use bevy_editor_ai_test::editor::resources::EditorState;
+    use bevy_editor_ai_test::editor::text_field::TextField;
+
+    #[test]
+    fn test_command_palette_runs_fuzzy_matches() {
+        #[derive(Resource)]
+        struct Ran;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<TypedText>();
+        app.init_resource::<EditorState>();
+        app.init_resource::<EditorCommands>();
+        app.add_systems(Update, command_palette_system);
+        app.world_mut().resource_mut::<EditorCommands>().register("Test: Run Plugin Command", |world| world.insert_resource(Ran));
+        let player = app.world_mut().spawn(Name::new("Player")).id();
+
+        let run_palette = |app: &mut App, search: &str| {
+            OpenCommandPalette.apply(app.world_mut());
+            let input = app.world_mut().query_filtered::<Entity, With<CommandPaletteInput>>().single(app.world()).unwrap();
+            assert_eq!(app.world().resource::<EditorState>().focused_input, Some(input));
+            app.world_mut().get_mut::<TextField>(input).unwrap().set_text(search);
+            app.update();
+            let listed = app.world_mut().query::<&CommandPaletteEntry>().iter(app.world()).count();
+            app.world_mut().resource_mut::<TypedText>().0 = vec![TextEdit::Submit];
+            app.update();
+            app.world_mut().resource_mut::<TypedText>().0.clear();
+            app.update();
+            listed
+        };
+
+        assert!(run_palette(&mut app, "plugin cmd") >= 1);
+        assert!(app.world().contains_resource::<Ran>(), "Registered commands run on Enter");
+        assert!(app.world_mut().query::<&CommandPalette>().iter(app.world()).next().is_none(), "The palette closes after running");
+        assert_eq!(app.world().resource::<EditorState>().focused_input, None);
+
+        run_palette(&mut app, "focus playr");
+        assert_eq!(app.world().resource::<EditorState>().selected_entity, Some(player));
+    }
+}
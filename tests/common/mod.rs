@@ -0,0 +1,69 @@
+//! Helpers shared by the integration tests. Each test crate uses a different subset.
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bevy_editor_ai_test::editor::menu::LastSavedScene;
+use bevy_editor_ai_test::editor::scene_format::SceneId;
+use std::path::{Path, PathBuf};
+
+/// A directory in the system temp dir, removed when dropped so a failing assert doesn't leave it
+/// behind.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn dir(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl std::ops::Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+/// MinimalPlugins, the scene types most tests save and somewhere for `save_system` to save to.
+pub fn scene_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<LastSavedScene>();
+    app.register_type::<Name>()
+        .register_type::<Transform>()
+        .register_type::<ChildOf>()
+        .register_type::<Children>()
+        .register_type::<SceneId>();
+    app
+}
+
+/// A RON scene file with one named entity per `(scene id, name)`.
+pub fn named_scene(names: &[(u32, &str)]) -> String {
+    let entities: String = names
+        .iter()
+        .map(|(id, name)| format!("    {}: (components: {{ \"bevy_ecs::name::Name\": \"{}\" }}),\n", id, name))
+        .collect();
+    format!("(\n  version: 2,\n  resources: {{}},\n  entities: {{\n{}  }},\n)", entities)
+}
+
+/// The sorted names of every named entity.
+pub fn names(app: &mut App) -> Vec<String> {
+    let world = app.world_mut();
+    let mut names: Vec<String> = world.query::<&Name>().iter(world).map(|n| n.to_string()).collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,68 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use crate::common::{named_scene, names, TempPath};
+    use bevy_editor_ai_test::editor::menu::{LoadRequest, SceneInfo};
+    use bevy_editor_ai_test::editor::resources::EditorState;
+    use bevy_editor_ai_test::editor::scene_asset::{SceneAssets, SceneSourcePlugin};
+    use bevy_editor_ai_test::editor::EditorPlugin;
+
+    /// An app with the editor and the plugins it needs besides MinimalPlugins, without a window.
+    fn editor_app(editor: EditorPlugin) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, SceneSourcePlugin, AssetPlugin::default()));
+        app.add_plugins((
+            bevy::input::InputPlugin,
+            bevy::scene::ScenePlugin,
+            bevy::window::WindowPlugin { primary_window: None, close_when_requested: false, ..default() },
+        ));
+        app.init_asset::<Font>();
+        app.add_plugins(EditorPlugin { preferences_path: None, keymap_path: None, ..editor });
+        app
+    }
+
+    #[test]
+    fn test_editor_plugin_runs_with_minimal_plugins() {
+        let mut app = editor_app(EditorPlugin::default());
+        app.update();
+        app.update();
+        assert!(app.world().contains_resource::<EditorState>());
+    }
+
+    #[test]
+    fn test_editor_plugin_opens_its_initial_scene() {
+        let dir = TempPath::dir("initial_scene_test");
+        let scene = dir.join("level.scn.ron");
+        std::fs::write(&scene, named_scene(&[(1, "Door"), (2, "Lamp")])).unwrap();
+
+        let mut app = editor_app(EditorPlugin { initial_scene: Some(scene.clone()), ..default() });
+        app.update();
+        for _ in 0..500 {
+            if !app.world().contains_resource::<LoadRequest>() && app.world().resource::<SceneAssets>().pending.is_none() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            app.update();
+        }
+        assert_eq!(app.world().resource::<SceneInfo>().file_path.as_deref(), Some(scene.as_path()));
+        assert_eq!(names(&mut app), ["Door", "Lamp"]);
+    }
+
+    #[test]
+    fn test_editor_plugin_keeps_a_missing_initial_scene_as_the_file_to_save() {
+        let dir = TempPath::dir("missing_initial_test");
+        let scene = dir.join("missing").join("level.scn.ron");
+
+        let mut app = editor_app(EditorPlugin { initial_scene: Some(scene.clone()), ..default() });
+        assert!(!app.world().contains_resource::<LoadRequest>(), "A missing scene shouldn't be loaded");
+        app.update();
+        app.update();
+        let info = app.world().resource::<SceneInfo>();
+        assert_eq!(info.file_path.as_deref(), Some(scene.as_path()));
+        assert!(!info.is_dirty);
+        assert!(app.world().resource::<SceneAssets>().pending.is_none());
+        assert!(names(&mut app).is_empty());
+    }
+}
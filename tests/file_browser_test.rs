@@ -0,0 +1,69 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use crate::common::TempPath;
+    use bevy_editor_ai_test::editor::file_dialog::{
+        file_browser_system, update_file_browser_list, FileBrowser, FileBrowserDialogs, FileBrowserNameInput,
+        FileDialogKind, FileDialogProvider, FileDialogRequest, FilePurpose,
+    };
+    use bevy_editor_ai_test::editor::input::{text_input_system, TextEdit, TypedText};
+    use bevy_editor_ai_test::editor::resources::{EditorState, InspectorUiState};
+    use bevy_editor_ai_test::editor::text_field::{EditorClipboard, MemoryClipboard, TextField};
+
+    #[derive(Resource, Default)]
+    struct BrowserChanges(usize);
+
+    fn count_browser_changes(browsers: Query<(), Changed<FileBrowser>>, mut changes: ResMut<BrowserChanges>) {
+        changes.0 += browsers.iter().count();
+    }
+
+    #[test]
+    fn test_file_browser_name_is_a_text_field() {
+        let dir = TempPath::dir("bevy_editor_file_browser_test");
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.add_message::<bevy::input::mouse::MouseWheel>();
+        app.init_resource::<TypedText>();
+        app.init_resource::<EditorState>();
+        app.init_resource::<InspectorUiState>();
+        app.insert_resource(EditorClipboard(Box::new(MemoryClipboard::default())));
+        app.init_resource::<BrowserChanges>();
+        app.add_systems(Update, (
+            text_input_system,
+            file_browser_system.after(text_input_system),
+            update_file_browser_list.after(file_browser_system),
+            count_browser_changes.after(update_file_browser_list),
+        ));
+
+        let mut request = FileDialogRequest::new(FileDialogKind::Save, FilePurpose::SaveScene { then: None });
+        request.directory = dir.to_path_buf();
+        FileBrowserDialogs.open(request, app.world_mut());
+        app.update();
+        app.update();
+        let name_input = app.world_mut().query_filtered::<Entity, With<FileBrowserNameInput>>().single(app.world()).unwrap();
+        assert_eq!(app.world().resource::<EditorState>().focused_input, Some(name_input), "The name should take the typing");
+
+        app.world_mut().resource_mut::<BrowserChanges>().0 = 0;
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<BrowserChanges>().0, 0, "An idle browser shouldn't be touched every frame");
+
+        app.world_mut().resource_mut::<TypedText>().0 = vec![TextEdit::Insert("levx".into()), TextEdit::Backspace];
+        app.update();
+        app.world_mut().resource_mut::<TypedText>().0 = vec![TextEdit::Insert("e".into())];
+        app.update();
+        assert_eq!(app.world().get::<TextField>(name_input).unwrap().text, "leve");
+        assert_eq!(app.world().resource::<BrowserChanges>().0, 0, "Typing the name shouldn't refresh the list");
+
+        app.world_mut().resource_mut::<TypedText>().0 = vec![TextEdit::Insert("l".into()), TextEdit::Submit];
+        app.update();
+        let mut browsers = app.world_mut().query::<&FileBrowser>();
+        let answer = browsers.single(app.world()).unwrap().answer.clone();
+        assert_eq!(answer, Some(Some(dir.join("level.scn.ron"))));
+    }
+}
@@ -0,0 +1,49 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::input::keyboard::{Key, KeyboardInput};
+    use bevy::input::ButtonState;
+    use bevy::window::Ime;
+    use crate::common::TempPath;
+    use bevy_editor_ai_test::editor::input::{collect_typed_text, TypedText};
+    use bevy_editor_ai_test::editor::keymap::{EditorAction, EditorKeymap, KeyChord};
+
+    #[test]
+    fn test_keymap_file_overrides_default_bindings() {
+        let dir = TempPath::dir("editor_keymap_test");
+        let path = dir.join("editor_keymap.ron");
+        std::fs::write(&path, "(bindings: { CameraForward: [(key: KeyZ)], TextSubmit: [(key: Enter, ctrl: true)] })").unwrap();
+        let keymap = EditorKeymap::load(&path);
+        assert_eq!(keymap.chords(EditorAction::CameraForward), &[KeyChord::new(KeyCode::KeyZ)]);
+        assert_eq!(keymap.chords(EditorAction::ToggleEditor), &[KeyChord::new(KeyCode::F1)], "Unlisted actions keep their defaults");
+        assert_eq!(keymap.describe(EditorAction::TextSubmit), "Ctrl+Enter");
+        assert_eq!(EditorKeymap::load(&dir.join("missing.ron")), EditorKeymap::default());
+
+        // Text fields follow the keymap too
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<KeyboardInput>();
+        app.add_message::<Ime>();
+        app.init_resource::<TypedText>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(keymap);
+        app.add_systems(Update, collect_typed_text);
+        let enter = KeyboardInput {
+            key_code: KeyCode::Enter,
+            logical_key: Key::Enter,
+            state: ButtonState::Pressed,
+            text: Some("\r".into()),
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        };
+        app.world_mut().write_message(enter.clone());
+        app.update();
+        assert!(!app.world().resource::<TypedText>().submitted(), "Plain Enter is no longer bound");
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ControlRight);
+        app.world_mut().write_message(enter);
+        app.update();
+        assert!(app.world().resource::<TypedText>().submitted());
+    }
+}
@@ -1,7 +1,45 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
     use bevy::prelude::*;
-    use bevy_editor_ai_test::editor::menu::{save_system, LastSavedScene, SaveRequest};
+    use crate::common::{named_scene, names, scene_app, TempPath};
+    use bevy_editor_ai_test::editor::menu::{
+        handle_file_dialog_results, import_system, parse_scene_file, poll_save_tasks, run_pending_action, save_system,
+        save_to_file_system, serialize_world_scene, write_atomic, GuardedAction, ImportRequest, LastSavedScene, LoadRequest,
+        PendingAction, SaveRequest, SaveStatus, SaveTasks, SceneInfo,
+    };
+    use bevy_editor_ai_test::editor::autosave::{
+        autosave_system, recovery_is_newer, recovery_path, AutosaveSettings, AutosaveState,
+    };
+    use bevy_editor_ai_test::editor::migration::SceneMigrations;
+    use bevy_editor_ai_test::editor::ui::setup_editor_ui;
+    use bevy_editor_ai_test::editor::scene_format::{
+        deserialize_scene, deserialize_scene_file, serialize_scene, SceneEncoding, SceneFormatError, SceneId,
+        SceneLoadMode, SceneSaveOptions, UnknownComponents,
+    };
+    use bevy::ecs::system::Command;
+    use bevy_editor_ai_test::editor::actions::PropertyChangeCommand;
+    use bevy_editor_ai_test::editor::camera::setup_editor_cameras;
+    use bevy_editor_ai_test::editor::prefab::{InstantiatePrefabCommand, PrefabLink, RevertOverrideCommand, SavePrefabCommand};
+    use bevy_editor_ai_test::editor::preferences::EditorPreferences;
+    use bevy_editor_ai_test::editor::file_dialog::{
+        poll_file_dialogs, FileDialogKind, FileDialogRequest, FileDialogResult, FileDialogs, FilePurpose, OpenFileDialog,
+        ScriptedFileDialogs,
+    };
+    use bevy_editor_ai_test::editor::resources::{EditorConfig, EditorState};
+    use bevy_editor_ai_test::editor::scene_asset::{
+        finish_scene_load, load_system, ApplySceneFile, watch_scene_file, SceneAssets, SceneFile, SceneFileLoader, SceneSourcePlugin,
+        SceneWatch,
+    };
+    use bevy_editor_ai_test::editor::dialog::{DialogButton, DialogChoice, ModalDialog};
+    use bevy_editor_ai_test::editor::command_palette::{Primitive, SpawnPrimitive};
+    use bevy_editor_ai_test::editor::primitives::{build_primitive_meshes, PrimitiveMesh, PrimitiveShape};
+    use bevy_editor_ai_test::editor::scene_tools::{headless_app, list_scene, resave_scene, validate_scene, SceneToolError};
+    use bevy_editor_ai_test::editor::templates::{discover_templates, NewSceneCommand, SceneTemplate, TEMPLATES_DIR};
+    use bevy_editor_ai_test::editor::save_filter::{
+        propagate_editor_only_to_child, propagate_editor_only_to_descendants, EditorOnly, ReflectSkipSave, SaveFilter,
+    };
 
     #[test]
     fn test_save_flow_emits_resource_logic() {
@@ -31,7 +69,801 @@ mod tests {
 
         // Check resource
         let saved = app.world().resource::<LastSavedScene>();
-        assert!(!saved.0.is_empty(), "Should have saved scene data to resource");
-        assert!(saved.0.contains("Transform"), "Should contain Transform component data");
+        let saved = String::from_utf8_lossy(&saved.0);
+        assert!(!saved.is_empty(), "Should have saved scene data to resource");
+        assert!(saved.contains("Transform"), "Should contain Transform component data");
+    }
+
+    const SCENE_WITH_UNKNOWN_COMPONENT: &str = r#"(
+  resources: {},
+  entities: {
+    4294967258: (
+      components: {
+        "bevy_transform::components::transform::Transform": (
+          translation: (1.0, 2.0, 3.0),
+          rotation: (0.0, 0.0, 0.0, 1.0),
+          scale: (1.0, 1.0, 1.0),
+        ),
+        "my_game::combat::Health": (current: 10, max: 20),
+      },
+    ),
+  },
+)"#;
+
+    fn test_registry() -> AppTypeRegistry {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Transform>();
+            registry.register::<UnknownComponents>();
+        }
+        registry
+    }
+
+    #[test]
+    fn test_lenient_load_preserves_unknown_components() {
+        let registry = test_registry();
+        let registry = registry.read();
+
+        let loaded = deserialize_scene(SCENE_WITH_UNKNOWN_COMPONENT, &registry, &SceneMigrations::default(), SceneLoadMode::Lenient)
+            .expect("Lenient load should skip unknown components");
+        assert_eq!(loaded.unknown_types.get("my_game::combat::Health"), Some(&1));
+        assert_eq!(loaded.scene.entities.len(), 1);
+        assert_eq!(loaded.scene.entities[0].components.len(), 3, "Transform, SceneId and preserved unknowns");
+
+        let saved = serialize_scene(&loaded.scene, &registry, &SceneSaveOptions::default()).expect("Scene should serialize");
+        assert!(saved.contains(r#""my_game::combat::Health": (current: 10, max: 20)"#), "Unknown component should be written back unchanged:\n{}", saved);
+        assert!(saved.contains("translation: (1.0, 2.0, 3.0)"));
+        assert!(!saved.contains("UnknownComponents"));
+    }
+
+    #[test]
+    fn test_lenient_load_reports_dropped_resources() {
+        let registry = test_registry();
+        let registry = registry.read();
+
+        let input = SCENE_WITH_UNKNOWN_COMPONENT.replace("resources: {}", r#"resources: { "my_game::Score": (points: 3) }"#);
+        let loaded = deserialize_scene(&input, &registry, &SceneMigrations::default(), SceneLoadMode::Lenient)
+            .expect("Lenient load should skip unknown resources");
+        assert_eq!(loaded.dropped_resources, vec!["my_game::Score".to_string()]);
+        assert!(!loaded.unknown_types.contains_key("my_game::Score"), "Dropped resources aren't preserved components");
+        assert!(loaded.scene.resources.is_empty());
+
+        let result = deserialize_scene(&input, &registry, &SceneMigrations::default(), SceneLoadMode::Strict);
+        assert!(matches!(result, Err(SceneFormatError::UnknownType(ref path)) if path == "my_game::Score"));
+    }
+
+    #[test]
+    fn test_strict_load_rejects_unknown_components() {
+        let registry = test_registry();
+        let registry = registry.read();
+
+        let result = deserialize_scene(SCENE_WITH_UNKNOWN_COMPONENT, &registry, &SceneMigrations::default(), SceneLoadMode::Strict);
+        assert!(matches!(result, Err(SceneFormatError::UnknownType(ref path)) if path == "my_game::combat::Health"));
+    }
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[test]
+    fn test_migrations_rename_types_and_fields() {
+        let registry = test_registry();
+        registry.write().register::<Visibility>();
+        registry.write().register::<Health>();
+        let registry = registry.read();
+
+        // Version 0 (no header), with a pre-0.17 Bevy type path and an old field name.
+        let scene = format!(r#"(
+  resources: {{}},
+  entities: {{
+    4294967258: (
+      components: {{
+        "bevy_render::view::visibility::Visibility": Hidden,
+        "{}": (hp: 7, max: 10),
+      }},
+    ),
+  }},
+)"#, Health::type_path());
+
+        let mut migrations = SceneMigrations::default();
+        migrations.rename_field("my_game", 1, Health::type_path(), "hp", "current");
+
+        let loaded = deserialize_scene(&scene, &registry, &migrations, SceneLoadMode::Strict)
+            .expect("Migrated scene should load strictly");
+        let components = &loaded.scene.entities[0].components;
+        assert!(components.iter().any(|c| c.try_downcast_ref::<Visibility>() == Some(&Visibility::Hidden)));
+        assert!(components.iter().any(|c| c.try_downcast_ref::<Health>() == Some(&Health { current: 7, max: 10 })));
+
+        let options = SceneSaveOptions { migrations: migrations.plugin_versions(), ..default() };
+        let saved = serialize_scene(&loaded.scene, &registry, &options).unwrap();
+        assert!(saved.contains("version: 2,") && saved.contains(r#""my_game": 1"#), "Saved scene should carry both versions:\n{}", saved);
+
+        // Already at the current version: migrations must not run again.
+        let mut transforms = SceneMigrations::empty();
+        transforms.transform_value("my_game", 1, Health::type_path(), |_| Err("should not run".to_string()));
+        assert!(deserialize_scene(&saved, &registry, &transforms, SceneLoadMode::Strict).is_ok());
+    }
+
+    /// Runs frames until the scene writes started by the first one are done.
+    fn finish_saves(app: &mut App) {
+        app.update();
+        for _ in 0..500 {
+            if app.world().resource::<SaveTasks>().0.is_empty() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            app.update();
+        }
+        panic!("Save did not finish");
+    }
+
+    fn finish_autosave(app: &mut App) {
+        app.update();
+        for _ in 0..500 {
+            if app.world().resource::<AutosaveState>().task.is_none() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            app.update();
+        }
+        panic!("Autosave did not finish");
+    }
+
+    fn save_world(app: &mut App) -> String {
+        app.world_mut().insert_resource(SaveRequest);
+        app.update();
+        String::from_utf8(app.world().resource::<LastSavedScene>().0.clone()).expect("RON saves are text")
+    }
+
+    #[test]
+    fn test_saves_are_deterministic() {
+        let mut app = scene_app();
+        app.init_resource::<EditorConfig>();
+        app.register_type::<Entity>().register_type::<Stats>();
+        app.add_systems(PostUpdate, save_system);
+
+        // Push the runtime entity indices away from zero so ids can't match them by accident.
+        for _ in 0..5 {
+            let entity = app.world_mut().spawn_empty().id();
+            app.world_mut().despawn(entity);
+        }
+        let parent = app.world_mut().spawn(Transform::from_xyz(1.0, 0.0, 0.0)).id();
+        app.world_mut().spawn((Transform::from_xyz(0.0, 2.0, 0.0), ChildOf(parent), Stats { speed: 2.0, ..default() }));
+
+        let first = save_world(&mut app);
+        let second = save_world(&mut app);
+        assert_eq!(first, second, "Saving an unchanged world should produce identical text");
+        assert!(first.contains("\n    1: (") && first.contains("\n    2: ("), "Entities should be keyed by scene id:\n{}", first);
+        assert!(first.contains("ChildOf\": (1)"), "Entity references should use scene ids:\n{}", first);
+
+        app.world_mut().resource_mut::<EditorConfig>().omit_default_fields = true;
+        let compact = save_world(&mut app);
+        assert!(!compact.contains("scale"), "Default fields should be omitted:\n{}", compact);
+        assert!(compact.contains("translation: (1.0, 0.0, 0.0)"));
+        assert!(compact.contains("speed: 2.0") && !compact.contains("label"), "Reflected structs omit defaults too:\n{}", compact);
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        let loaded = deserialize_scene(&compact, &registry, &SceneMigrations::default(), SceneLoadMode::Strict)
+            .expect("Scene with omitted fields should load");
+        let transforms: Vec<Transform> = loaded.scene.entities.iter()
+            .flat_map(|e| e.components.iter().filter_map(|c| c.try_downcast_ref::<Transform>().copied()))
+            .collect();
+        assert_eq!(transforms, vec![Transform::from_xyz(1.0, 0.0, 0.0), Transform::from_xyz(0.0, 2.0, 0.0)]);
+        let stats = loaded.scene.entities.iter().flat_map(|e| &e.components).find_map(|c| c.try_downcast_ref::<Stats>());
+        assert_eq!(stats, Some(&Stats { speed: 2.0, label: String::new() }));
+    }
+
+    #[test]
+    fn test_scene_ids_stay_with_their_entities() {
+        let mut app = scene_app();
+        app.add_systems(PostUpdate, save_system);
+        let id_of = |app: &mut App, name: &str| {
+            let world = app.world_mut();
+            let mut query = world.query::<(&Name, &SceneId)>();
+            query.iter(world).find(|(n, _)| n.as_str() == name).map(|(_, id)| *id)
+        };
+
+        let first = app.world_mut().spawn(Name::new("First")).id();
+        app.world_mut().spawn(Name::new("Second"));
+        app.world_mut().spawn(Name::new("Third"));
+        save_world(&mut app);
+        let ids = [id_of(&mut app, "Second"), id_of(&mut app, "Third")];
+        assert!(ids.iter().all(Option::is_some), "Saving should give new entities their id");
+
+        // The freed entity index is reused by the next spawn
+        app.world_mut().despawn(first);
+        app.world_mut().spawn(Name::new("Fourth"));
+        let saved = save_world(&mut app);
+        assert_eq!([id_of(&mut app, "Second"), id_of(&mut app, "Third")], ids, "Existing ids shouldn't change:\n{}", saved);
+        assert_eq!(id_of(&mut app, "Fourth"), Some(SceneId(4)), "New entities take the next unused id");
+    }
+
+    #[test]
+    fn test_saved_hierarchy_survives_reload() {
+        let mut app = scene_app();
+        app.insert_resource(SaveFilter {
+            denied: bevy_editor_ai_test::editor::save_filter::runtime_components().into_iter().collect(),
+        });
+        app.add_systems(PostUpdate, save_system);
+
+        let parent = app.world_mut().spawn(Name::new("Parent")).id();
+        app.world_mut().spawn((Name::new("Child"), ChildOf(parent)));
+        let saved = save_world(&mut app);
+
+        let registry = app.world().resource::<AppTypeRegistry>().clone();
+        let loaded = deserialize_scene(&saved, &registry.read(), &SceneMigrations::default(), SceneLoadMode::Strict)
+            .expect("Saved scene should load");
+        let mut world = World::new();
+        world.insert_resource(registry);
+        loaded.scene.write_to_world(&mut world, &mut Default::default()).expect("Scene should spawn");
+
+        let mut names = world.query::<(Entity, &Name)>();
+        let mut find = |world: &World, name: &str| {
+            names.iter(world).find(|(_, n)| n.as_str() == name).map(|(e, _)| e).unwrap()
+        };
+        let (parent, child) = (find(&world, "Parent"), find(&world, "Child"));
+        assert_eq!(world.get::<ChildOf>(child).map(ChildOf::parent), Some(parent));
+        assert_eq!(world.get::<Children>(parent).map(|c| c.to_vec()), Some(vec![child]), "Reloaded parent should list its child:\n{}", saved);
+    }
+
+    #[test]
+    fn test_binary_scenes_round_trip_like_ron() {
+        let mut app = scene_app();
+        app.register_type::<UnknownComponents>();
+        let registry = app.world().resource::<AppTypeRegistry>().clone();
+        app.add_systems(PostUpdate, save_system);
+
+        let loaded = deserialize_scene(SCENE_WITH_UNKNOWN_COMPONENT, &registry.read(), &SceneMigrations::default(), SceneLoadMode::Lenient)
+            .unwrap();
+        loaded.scene.write_to_world(app.world_mut(), &mut Default::default()).unwrap();
+        let parent = app.world_mut().spawn((Name::new("Parent"), Transform::from_xyz(0.0, 1.0, 0.0))).id();
+        app.world_mut().spawn((Name::new("Child"), ChildOf(parent)));
+
+        let ron = save_world(&mut app);
+        app.insert_resource(SceneInfo { file_path: Some("level.scn.bin".into()), is_dirty: true });
+        app.world_mut().insert_resource(SaveRequest);
+        app.update();
+        let binary = app.world().resource::<LastSavedScene>().0.clone();
+        assert_eq!(SceneEncoding::detect(&binary), SceneEncoding::Binary, "A .scn.bin path should save in binary");
+        assert!(binary.len() < ron.len(), "Binary ({} bytes) should be smaller than RON ({} bytes)", binary.len(), ron.len());
+
+        let registry = registry.read();
+        let from_binary = deserialize_scene_file(&binary, &registry, &SceneMigrations::default(), SceneLoadMode::Lenient)
+            .expect("Binary scene should load");
+        assert_eq!(from_binary.unknown_types.get("my_game::combat::Health"), Some(&1), "Unknown components should survive");
+        let resaved = serialize_scene(&from_binary.scene, &registry, &SceneSaveOptions::default()).unwrap();
+        assert_eq!(resaved, ron, "Loading the binary file should give the same scene as the RON file");
+
+        let from_ron = deserialize_scene_file(ron.as_bytes(), &registry, &SceneMigrations::default(), SceneLoadMode::Lenient);
+        assert!(from_ron.is_ok_and(|loaded| loaded.scene.entities.len() == 3), "RON files should still be detected");
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, SkipSave)]
+    struct CachedPath {
+        length: f32,
+    }
+
+    #[test]
+    fn test_save_filter_skips_runtime_data_and_editor_entities() {
+        let mut app = scene_app();
+        app.register_type::<GlobalTransform>().register_type::<CachedPath>();
+        let mut filter = SaveFilter::default();
+        filter.deny::<GlobalTransform>();
+        app.insert_resource(filter);
+        app.add_observer(propagate_editor_only_to_child);
+        app.add_systems(PostUpdate, save_system);
+
+        app.world_mut().spawn((Name::new("Player"), Transform::default(), CachedPath { length: 4.0 }));
+        let editor_panel = app.world_mut().spawn((Name::new("Editor Panel"), EditorOnly)).id();
+        app.world_mut().spawn((Name::new("Editor Label"), ChildOf(editor_panel)));
+
+        let saved = save_world(&mut app);
+        assert!(saved.contains("Player") && saved.contains("Transform"), "Scene entity should be saved:\n{}", saved);
+        assert!(!saved.contains("GlobalTransform"), "Deny-listed component should be skipped:\n{}", saved);
+        assert!(!saved.contains("CachedPath"), "SkipSave component should be skipped:\n{}", saved);
+        assert!(!saved.contains("Editor"), "EditorOnly entities and their children should be skipped:\n{}", saved);
+    }
+
+    #[test]
+    fn test_editor_entities_are_never_saved() {
+        let mut app = scene_app();
+        app.add_observer(propagate_editor_only_to_child);
+        app.add_observer(propagate_editor_only_to_descendants);
+        app.add_systems(Startup, (setup_editor_ui, setup_editor_cameras));
+        app.add_systems(PostUpdate, save_system);
+        app.update();
+
+        // A scene entity, plus an editor subtree tagged only after its children were attached.
+        app.world_mut().spawn(Name::new("Player"));
+        let panel = app.world_mut().spawn(Node::default()).with_child(Text::new("Late")).id();
+        app.world_mut().entity_mut(panel).insert(EditorOnly);
+
+        let saved = save_world(&mut app);
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        let loaded = deserialize_scene(&saved, &registry, &SceneMigrations::default(), SceneLoadMode::Lenient)
+            .expect("Saved scene should load");
+        assert_eq!(loaded.scene.entities.len(), 1, "Only the scene entity should be saved:\n{}", saved);
+    }
+
+    #[test]
+    fn test_import_adds_scene_under_new_parents() {
+        let dir = TempPath::dir("import_test");
+        let path = dir.join("crates.scn.ron");
+        std::fs::write(&path, r#"(
+  version: 2,
+  resources: {},
+  entities: {
+    1: (
+      components: {
+        "bevy_ecs::name::Name": "Crate",
+      },
+    ),
+  },
+)"#).unwrap();
+
+        let mut app = scene_app();
+        app.add_plugins((AssetPlugin::default(), bevy::scene::ScenePlugin));
+        app.init_resource::<SceneInfo>();
+        app.init_resource::<EditorConfig>();
+        app.init_resource::<SceneMigrations>();
+        app.add_systems(Update, import_system);
+
+        let existing = app.world_mut().spawn(Name::new("Existing")).id();
+        for _ in 0..2 {
+            app.world_mut().insert_resource(ImportRequest { path: path.clone(), under_parent: true });
+            app.update();
+            app.update();
+        }
+
+        let world = app.world_mut();
+        assert!(world.get_entity(existing).is_ok(), "Import must not clear the world");
+        let mut crates = world.query_filtered::<(&Name, &ChildOf), Without<SceneId>>();
+        let parents: Vec<Entity> = crates.iter(world).filter(|(name, _)| name.as_str() == "Crate").map(|(_, c)| c.parent()).collect();
+        assert_eq!(parents.len(), 2, "Each import should spawn its own copy without file scene ids");
+        assert_ne!(parents[0], parents[1]);
+        assert!(parents.iter().all(|p| world.get::<Name>(*p).is_some_and(|n| n.as_str() == "crates")));
+        assert!(app.world().resource::<SceneInfo>().is_dirty);
+    }
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default)]
+    struct Stats {
+        speed: f32,
+        label: String,
+    }
+
+    #[test]
+    fn test_prefab_edits_propagate_and_keep_overrides() {
+        let dir = TempPath::dir("prefab_test");
+        let path = dir.join("crate.prefab.ron");
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<EditorState>();
+        {
+            let mut registry = world.resource::<AppTypeRegistry>().write();
+            registry.register::<Name>();
+            registry.register::<ChildOf>();
+            registry.register::<Children>();
+            registry.register::<SceneId>();
+            registry.register::<PrefabLink>();
+            registry.register::<Stats>();
+        }
+
+        let root = world.spawn((Name::new("Crate"), Stats { speed: 1.0, label: "wood".to_string() })).id();
+        world.spawn((Name::new("Lid"), ChildOf(root)));
+        SavePrefabCommand { root, path: path.clone() }.apply(&mut world);
+
+        let instantiate = |world: &mut World| {
+            InstantiatePrefabCommand { path: path.clone(), parent: None }.apply(world);
+            world.resource::<EditorState>().selected_entity.expect("Instance root should be selected")
+        };
+        let a = instantiate(&mut world);
+        let b = instantiate(&mut world);
+        assert_eq!(world.get::<Children>(a).map(|c| c.len()), Some(1), "Instance should include the child");
+
+        PropertyChangeCommand {
+            entity: a,
+            component_type_id: std::any::TypeId::of::<Stats>(),
+            field_name: "speed".to_string(),
+            new_value: "5".to_string(),
+        }.apply(&mut world);
+        assert!(world.get::<PrefabLink>(a).unwrap().is_overridden(Stats::type_path(), "speed"));
+
+        // Edit the source and save the prefab again
+        *world.get_mut::<Stats>(root).unwrap() = Stats { speed: 2.0, label: "steel".to_string() };
+        SavePrefabCommand { root, path: path.clone() }.apply(&mut world);
+
+        assert_eq!(world.get::<Stats>(a), Some(&Stats { speed: 5.0, label: "steel".to_string() }), "Override should survive");
+        assert_eq!(world.get::<Stats>(b), Some(&Stats { speed: 2.0, label: "steel".to_string() }));
+
+        RevertOverrideCommand { entity: a, component: Stats::type_path().to_string(), field: "speed".to_string() }.apply(&mut world);
+        assert_eq!(world.get::<Stats>(a).map(|s| s.speed), Some(2.0));
+        assert!(world.get::<PrefabLink>(a).unwrap().overrides.is_empty());
+    }
+
+    #[test]
+    fn test_prefab_links_are_saved_relative_to_the_scene() {
+        let dir = std::path::absolute("project").unwrap();
+        let scene = dir.join("levels").join("level.scn.ron");
+        let prefab = dir.join("prefabs").join("crate.prefab.ron");
+
+        let mut app = scene_app();
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.register_type::<PrefabLink>();
+        app.add_systems(PostUpdate, save_system);
+        let link = PrefabLink { source: prefab.to_string_lossy().into_owned(), node: 1, overrides: Vec::new() };
+        app.world_mut().spawn(link);
+
+        let saved = save_world(&mut app);
+        assert!(saved.contains(r#"source: "../prefabs/crate.prefab.ron""#), "Link should be relative to the scene:\n{}", saved);
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        let loaded = parse_scene_file(saved.as_bytes(), &scene, &registry, &SceneMigrations::default(), SceneLoadMode::Strict)
+            .expect("Saved scene should load");
+        let sources: Vec<String> = loaded.entities.iter()
+            .flat_map(|e| e.components.iter().filter_map(|c| c.try_downcast_ref::<PrefabLink>().map(|l| l.source.clone())))
+            .collect();
+        assert_eq!(sources, vec![prefab.to_string_lossy().into_owned()], "Links should resolve against the scene's directory");
+    }
+
+    #[test]
+    fn test_autosave_writes_recovery_until_saved() {
+        let dir = TempPath::dir("autosave_test");
+        let scene = dir.join("level.scn.ron");
+        let recovery = recovery_path(Some(&scene));
+
+        let mut app = scene_app();
+        app.insert_resource(AutosaveSettings { enabled: true, interval: std::time::Duration::ZERO });
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.init_resource::<SaveTasks>();
+        app.add_systems(PostUpdate, (save_system, save_to_file_system, poll_save_tasks, autosave_system).chain());
+        let entity = app.world_mut().spawn(Transform::from_xyz(1.0, 2.0, 3.0)).id();
+
+        finish_autosave(&mut app);
+        assert!(app.world().resource::<SceneInfo>().is_dirty, "Autosave should not clear the dirty flag");
+        assert!(recovery_is_newer(Some(&scene), &recovery), "Recovery file should be newer than the missing scene");
+        assert!(std::fs::read_to_string(&recovery).unwrap().contains("Transform"));
+
+        std::fs::remove_file(&recovery).unwrap();
+        finish_autosave(&mut app);
+        assert!(!recovery.exists(), "An unchanged scene should not be written again");
+        app.world_mut().get_mut::<Transform>(entity).unwrap().translation.x = 7.0;
+        finish_autosave(&mut app);
+        assert!(std::fs::read_to_string(&recovery).unwrap().contains("7.0"), "A changed scene should be written again");
+
+        app.world_mut().insert_resource(SaveRequest);
+        finish_saves(&mut app);
+        let saved = std::fs::read_to_string(&scene).ok();
+        assert!(saved.is_some_and(|s| s.contains("Transform")));
+        assert!(!recovery.exists(), "Saving should remove the recovery file");
+        assert!(!recovery_is_newer(Some(&scene), &recovery));
+    }
+
+    #[test]
+    fn test_loading_a_scene_offers_its_recovery_file() {
+        let dir = TempPath::dir("recovery_load_test");
+        let scene = dir.join("level.scn.ron");
+        let recovery = recovery_path(Some(&scene));
+        let contents = named_scene(&[(1, "Door")]);
+        std::fs::write(&scene, &contents).unwrap();
+        std::fs::write(&recovery, named_scene(&[(1, "Door"), (2, "Lamp")])).unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&recovery).unwrap().set_modified(later).unwrap();
+
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SceneInfo>();
+        world.resource::<AppTypeRegistry>().write().register::<Name>();
+        world.resource::<AppTypeRegistry>().write().register::<SceneId>();
+        ApplySceneFile { bytes: contents.into_bytes(), source: scene.clone(), is_restore: false, selected: None }.apply(&mut world);
+
+        let mut choices = world.query::<&DialogButton>();
+        let choices: Vec<DialogChoice> = choices.iter(&world).map(|button| button.0.clone()).collect();
+        assert!(choices.contains(&DialogChoice::RestoreRecovery(recovery)), "Load should offer the newer recovery file");
+    }
+
+    #[test]
+    fn test_exit_waits_for_unsaved_changes() {
+        let dir = TempPath::dir("exit_guard_test");
+        let scene = dir.join("level.scn.ron");
+        let mut app = scene_app();
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.init_resource::<SaveTasks>();
+        app.add_systems(PostUpdate, (save_system, save_to_file_system, poll_save_tasks, run_pending_action).chain());
+        app.world_mut().spawn(Transform::default());
+
+        // A dirty scene is never thrown away unless the user chose to discard it
+        app.world_mut().insert_resource(PendingAction { action: GuardedAction::Exit, discard_changes: false });
+        app.update();
+        assert!(app.should_exit().is_none());
+        assert!(!app.world().contains_resource::<PendingAction>(), "Refused action should be dropped");
+
+        // "Save" saves first, then exits
+        app.world_mut().insert_resource(SaveRequest);
+        app.world_mut().insert_resource(PendingAction { action: GuardedAction::Exit, discard_changes: false });
+        finish_saves(&mut app);
+        let saved = std::fs::read_to_string(&scene).ok();
+        assert!(saved.is_some_and(|s| s.contains("Transform")));
+        assert_eq!(app.should_exit(), Some(AppExit::Success));
+    }
+
+    #[test]
+    fn test_new_scene_from_template_file() {
+        let templates = discover_templates(std::path::Path::new(TEMPLATES_DIR));
+        let outdoor = SceneTemplate::File(std::path::Path::new(TEMPLATES_DIR).join("outdoor.scn.ron"));
+        assert_eq!(templates[..2], [SceneTemplate::Empty, SceneTemplate::Basic]);
+        assert!(templates.contains(&outdoor), "Templates folder should be listed: {:?}", templates);
+
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let registry = world.resource::<AppTypeRegistry>().clone();
+            let mut registry = registry.write();
+            registry.register::<Name>();
+            registry.register::<Transform>();
+            registry.register::<DirectionalLight>();
+            registry.register::<SceneId>();
+        }
+        world.insert_resource(EditorConfig { scene_load_mode: SceneLoadMode::Strict, ..default() });
+        world.insert_resource(SceneInfo { file_path: Some("old.scn.ron".into()), is_dirty: true });
+        let old = world.spawn(Name::new("Old")).id();
+
+        NewSceneCommand { template: outdoor }.apply(&mut world);
+
+        assert!(world.get_entity(old).is_err(), "Old scene should be cleared");
+        let info = world.resource::<SceneInfo>();
+        assert!(info.file_path.is_none() && !info.is_dirty);
+        let mut names: Vec<String> = world.query::<&Name>().iter(&world).map(|n| n.to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["Spawn Point", "Sun"]);
+        assert_eq!(world.query::<&SceneId>().iter(&world).count(), 0, "Template ids should not carry over");
+
+        NewSceneCommand { template: SceneTemplate::Basic }.apply(&mut world);
+        let mut names: Vec<String> = world.query::<&Name>().iter(&world).map(|n| n.to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["Camera", "Ground", "Light"]);
+        let ground = world.query::<&PrimitiveMesh>().iter(&world).map(|p| p.shape).collect::<Vec<_>>();
+        assert_eq!(ground, [PrimitiveShape::Plane { size: Vec2::splat(10.0) }], "The ground should be saveable");
+        let cameras: Vec<bool> = world.query::<&Camera>().iter(&world).map(|camera| camera.is_active).collect();
+        assert_eq!(cameras, [false], "The scene camera shouldn't cover the editor viewport");
+
+        {
+            let registry = world.resource::<AppTypeRegistry>().clone();
+            let mut registry = registry.write();
+            registry.register::<Camera>();
+            registry.register::<Camera3d>();
+        }
+        world.insert_resource(SaveFilter {
+            denied: bevy_editor_ai_test::editor::save_filter::runtime_components().into_iter().collect(),
+        });
+        let saved = serialize_world_scene(&mut world).expect("The basic template should save");
+        assert!(
+            saved.contains("Camera3d") && saved.contains("is_active: false"),
+            "The camera should be saved, still inactive:\n{}",
+            saved
+        );
+    }
+
+    #[test]
+    fn test_saved_scenes_become_recent_and_persist() {
+        let dir = TempPath::dir("recent_test");
+        let scene = dir.join("level.scn.ron");
+        let gone = dir.join("gone.scn.ron");
+
+        let mut app = scene_app();
+        app.insert_resource(EditorPreferences { recent_scenes: vec![gone.clone(), scene.clone()] });
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.init_resource::<SaveTasks>();
+        app.add_systems(PostUpdate, (save_system, save_to_file_system, poll_save_tasks).chain());
+        app.world_mut().insert_resource(SaveRequest);
+        finish_saves(&mut app);
+        assert_eq!(app.world().resource::<EditorPreferences>().recent_scenes, [scene.clone(), gone.clone()], "Saved scene should move to the front");
+
+        let prefs_path = dir.join("editor_preferences.ron");
+        app.world().resource::<EditorPreferences>().save(&prefs_path).unwrap();
+        let loaded = EditorPreferences::load(&prefs_path);
+        assert_eq!(loaded.recent_scenes, [scene], "Missing scenes should be pruned on load");
+        assert_eq!(EditorPreferences::load(&dir.join("missing.ron")), EditorPreferences::default());
+    }
+
+    #[test]
+    fn test_save_and_load_through_scripted_file_dialogs() {
+        let dir = TempPath::dir("file_dialog_test");
+        let scene = dir.join("level.scn.ron");
+        let mut app = scene_app();
+        app.init_resource::<EditorConfig>();
+        app.init_resource::<SceneInfo>();
+        app.add_message::<FileDialogResult>();
+        app.insert_resource(FileDialogs(Box::new(ScriptedFileDialogs::new([Some(scene.clone()), None, Some(scene.clone()), Some(scene.clone())]))));
+        app.add_systems(Update, (poll_file_dialogs, handle_file_dialog_results).chain());
+        app.init_resource::<SaveTasks>();
+        app.add_systems(PostUpdate, (save_system, save_to_file_system, poll_save_tasks).chain());
+        app.world_mut().spawn(Transform::default());
+
+        let open = |app: &mut App, kind, purpose| {
+            OpenFileDialog(FileDialogRequest::new(kind, purpose)).apply(app.world_mut());
+            finish_saves(app);
+        };
+
+        open(&mut app, FileDialogKind::Save, FilePurpose::SaveScene { then: None });
+        let saved = std::fs::read_to_string(&scene).ok();
+        assert_eq!(app.world().resource::<SceneInfo>().file_path.as_ref(), Some(&scene));
+        assert!(saved.is_some_and(|s| s.contains("Transform")));
+
+        // A cancelled dialog does nothing
+        open(&mut app, FileDialogKind::Open, FilePurpose::LoadScene);
+        assert!(app.world().get_resource::<PendingAction>().is_none());
+
+        app.add_systems(PostUpdate, run_pending_action.after(poll_save_tasks));
+        open(&mut app, FileDialogKind::Open, FilePurpose::LoadScene);
+        assert!(app.world().contains_resource::<LoadRequest>(), "Picking a scene on a clean editor should load it");
+
+        open(&mut app, FileDialogKind::Open, FilePurpose::ImportScene { under_parent: true });
+        let import = app.world().get_resource::<ImportRequest>();
+        assert!(import.is_some_and(|r| r.path == scene && r.under_parent), "Import Under Parent should ask for a parent");
+    }
+
+    #[test]
+    fn test_failed_save_is_reported_and_keeps_changes() {
+        let dir = TempPath::dir("missing_dir_test");
+        let scene = dir.join("missing").join("level.scn.ron");
+        let mut app = scene_app();
+        app.init_resource::<SaveTasks>();
+        app.init_resource::<SaveStatus>();
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.add_systems(PostUpdate, (save_system, save_to_file_system, poll_save_tasks).chain());
+        app.world_mut().spawn(Transform::default());
+
+        app.world_mut().insert_resource(SaveRequest);
+        finish_saves(&mut app);
+        let status = app.world().resource::<SaveStatus>();
+        assert!(status.is_error && status.message.starts_with("Save failed"), "{:?}", status);
+        assert!(app.world().resource::<SceneInfo>().is_dirty, "Unwritten changes should stay dirty");
+    }
+
+    #[test]
+    fn test_concurrent_atomic_writes_to_one_path() {
+        let dir = TempPath::dir("write_atomic_test");
+        let path = dir.join("level.scn.ron");
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, format!("write {}", i).repeat(1000).as_bytes()))
+            })
+            .collect();
+        let results: Vec<_> = writers.into_iter().map(|w| w.join().unwrap()).collect();
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let files = std::fs::read_dir(&dir).map_or(0, |entries| entries.count());
+
+        assert!(results.iter().all(Result::is_ok), "Concurrent writes should all succeed: {:?}", results);
+        assert!((0..8).any(|i| contents == format!("write {}", i).repeat(1000)), "The file should hold one whole write");
+        assert_eq!(files, 1, "No temporary files should be left behind");
+    }
+
+    #[test]
+    fn test_load_through_asset_server_and_reload_keeps_selection() {
+        let dir = TempPath::dir("asset_load_test");
+        let scene = dir.join("level.scn.ron");
+        std::fs::write(&scene, named_scene(&[(1, "Door"), (2, "Lamp")])).unwrap();
+
+        let mut app = scene_app();
+        app.add_plugins((SceneSourcePlugin, AssetPlugin::default()));
+        app.init_asset::<SceneFile>().init_asset_loader::<SceneFileLoader>();
+        app.init_resource::<EditorState>();
+        app.init_resource::<EditorConfig>();
+        app.init_resource::<SceneMigrations>();
+        app.init_resource::<SceneAssets>();
+        app.insert_resource(SceneWatch { interval: std::time::Duration::ZERO, ..default() });
+        app.insert_resource(SceneInfo { file_path: Some(scene.clone()), is_dirty: true });
+        app.add_systems(Update, (load_system, finish_scene_load, watch_scene_file).chain());
+
+        let load = |app: &mut App| {
+            app.world_mut().insert_resource(LoadRequest::default());
+            app.update();
+            for _ in 0..500 {
+                if app.world().resource::<SceneAssets>().pending.is_none() {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(2));
+                app.update();
+            }
+            panic!("Scene did not load");
+        };
+
+        load(&mut app);
+        assert_eq!(names(&mut app), ["Door", "Lamp"]);
+        assert!(!app.world().resource::<SceneInfo>().is_dirty);
+        let world = app.world_mut();
+        let lamp = world.query::<(Entity, &SceneId)>().iter(world).find(|(_, id)| id.0 == 2).map(|(e, _)| e);
+        app.world_mut().resource_mut::<EditorState>().selected_entity = lamp;
+
+        // Edit the file outside the editor
+        std::fs::write(&scene, named_scene(&[(1, "Door"), (2, "Lantern"), (3, "Rug")])).unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&scene).unwrap().set_modified(later).unwrap();
+
+        // A save of the editor's own still being written isn't an external change
+        let saving = bevy::tasks::IoTaskPool::get().spawn(std::future::pending::<std::io::Result<()>>());
+        app.world_mut().insert_resource(SaveTasks(vec![(scene.clone(), saving)]));
+        app.update();
+        assert_eq!(app.world_mut().query::<&ModalDialog>().iter(app.world()).count(), 0, "Pending saves should be skipped");
+        app.world_mut().resource_mut::<SaveTasks>().0.clear();
+
+        app.update();
+        assert_eq!(app.world_mut().query::<&ModalDialog>().iter(app.world()).count(), 1, "External change should offer a reload");
+
+        load(&mut app);
+        assert_eq!(names(&mut app), ["Door", "Lantern", "Rug"]);
+        let selected = app.world().resource::<EditorState>().selected_entity.expect("Selection should be kept");
+        assert_eq!(app.world().get::<Name>(selected).map(|n| n.as_str()), Some("Lantern"));
+    }
+
+    #[test]
+    fn test_headless_scene_tools() {
+        let dir = std::env::temp_dir().join(format!("scene_tools_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let with_unknown = dir.join("unknown.scn.ron");
+        std::fs::write(&with_unknown, SCENE_WITH_UNKNOWN_COMPONENT).unwrap();
+        let outdoor = std::path::Path::new(TEMPLATES_DIR).join("outdoor.scn.ron");
+
+        let mut app = headless_app();
+        let world = app.world_mut();
+        assert_eq!(validate_scene(world, &outdoor).ok(), Some(2));
+        assert!(matches!(
+            validate_scene(world, &with_unknown),
+            Err(SceneToolError::UnknownTypes(_, ref types)) if types == &["my_game::combat::Health".to_string()]
+        ));
+
+        // RON -> binary -> RON keeps everything, including the unknown component
+        let (ron, unknown) = resave_scene(world, &with_unknown, SceneEncoding::Ron).unwrap();
+        assert_eq!(unknown, ["my_game::combat::Health"]);
+        let binary_path = dir.join("unknown.scn.bin");
+        let (binary, _) = resave_scene(world, &with_unknown, SceneEncoding::Binary).unwrap();
+        std::fs::write(&binary_path, binary).unwrap();
+        let (normalized, _) = resave_scene(world, &binary_path, SceneEncoding::Ron).unwrap();
+        assert_eq!(String::from_utf8(normalized).unwrap(), String::from_utf8(ron).unwrap());
+
+        let listed = list_scene(world, &binary_path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, Some(1));
+        assert!(listed[0].components.contains(&"my_game::combat::Health?".to_string()), "{:?}", listed[0].components);
+        assert!(listed[0].components.contains(&"Transform".to_string()));
+    }
+
+    #[test]
+    fn test_spawned_primitives_keep_their_mesh_through_save_and_load() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>().init_asset::<StandardMaterial>();
+        app.init_resource::<LastSavedScene>();
+        app.init_resource::<SceneInfo>();
+        app.init_resource::<SceneMigrations>();
+        app.register_type::<PrimitiveMesh>().register_type::<Name>().register_type::<SceneId>();
+        app.insert_resource(SaveFilter {
+            denied: bevy_editor_ai_test::editor::save_filter::runtime_components().into_iter().collect(),
+        });
+        app.add_systems(Update, build_primitive_meshes);
+        app.add_systems(PostUpdate, save_system);
+
+        SpawnPrimitive(Primitive::Sphere).apply(app.world_mut());
+        let saved = save_world(&mut app);
+        let sphere = app.world_mut().query_filtered::<Entity, With<PrimitiveMesh>>().single(app.world()).unwrap();
+        assert!(app.world().get::<Mesh3d>(sphere).is_some(), "Spawning should build the mesh");
+        assert!(saved.contains("PrimitiveMesh") && !saved.contains("Mesh3d"), "Only the primitive should be saved:\n{}", saved);
+
+        app.world_mut().despawn(sphere);
+        ApplySceneFile { bytes: saved.into_bytes(), source: "primitives.scn.ron".into(), is_restore: true, selected: None }
+            .apply(app.world_mut());
+        app.update();
+        let mut loaded = app.world_mut().query::<(&PrimitiveMesh, Option<&Mesh3d>)>();
+        let loaded: Vec<_> = loaded.iter(app.world()).map(|(primitive, mesh)| (primitive.shape, mesh.is_some())).collect();
+        assert_eq!(loaded, vec![(PrimitiveShape::Sphere, true)], "Loading should rebuild the mesh");
     }
 }
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::input::keyboard::{Key, KeyboardInput};
+    use bevy::input::ButtonState;
+    use bevy::window::Ime;
+    use bevy_editor_ai_test::editor::components::{InspectorPanel, PropertyInput};
+    use bevy_editor_ai_test::editor::input::{
+        collect_typed_text, route_keyboard_input, text_input_system, EditorKeys, TextEdit, TypedText,
+    };
+    use bevy_editor_ai_test::editor::keymap::EditorKeymap;
+    use bevy_editor_ai_test::editor::resources::{EditorState, InspectorUiState};
+    use bevy_editor_ai_test::editor::text_field::{CaretMotion, EditorClipboard, MemoryClipboard, TextField};
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default)]
+    struct Stats {
+        speed: f32,
+        label: String,
+    }
+
+    fn key(key_code: KeyCode, logical_key: Key, text: Option<&str>, state: ButtonState) -> KeyboardInput {
+        KeyboardInput {
+            key_code,
+            logical_key,
+            state,
+            text: text.map(Into::into),
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        }
+    }
+
+    /// An app collecting keyboard and IME messages into [`TypedText`].
+    fn typing_app(keymap: EditorKeymap) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<KeyboardInput>();
+        app.add_message::<Ime>();
+        app.init_resource::<TypedText>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.insert_resource(keymap);
+        app.add_systems(Update, collect_typed_text);
+        app
+    }
+
+    #[test]
+    fn test_typed_text_follows_layout_and_ime() {
+        let mut app = typing_app(EditorKeymap::default());
+        let type_str = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                let typed = c.to_string();
+                app.world_mut().write_message(key(KeyCode::KeyA, Key::Character(typed.as_str().into()), Some(&typed), ButtonState::Pressed));
+                app.world_mut().write_message(key(KeyCode::KeyA, Key::Character(typed.as_str().into()), None, ButtonState::Released));
+            }
+        };
+
+        let mut value = String::new();
+        // Shifted and layout-specific characters arrive as text, not key codes
+        type_str(&mut app, "Door_2, -1.5e-3");
+        app.world_mut().write_message(key(KeyCode::Escape, Key::Escape, Some("\u{1b}"), ButtonState::Pressed));
+        app.world_mut().write_message(key(KeyCode::Backspace, Key::Backspace, Some("\u{8}"), ButtonState::Pressed));
+        app.world_mut().write_message(Ime::Commit { window: Entity::PLACEHOLDER, value: "扉".to_string() });
+        app.update();
+        let typed = app.world().resource::<TypedText>();
+        assert!(typed.apply(&mut value));
+        assert!(!typed.submitted());
+        assert_eq!(value, "Door_2, -1.5e-扉");
+
+        app.world_mut().write_message(key(KeyCode::NumpadEnter, Key::Enter, Some("\r"), ButtonState::Pressed));
+        app.update();
+        let typed = app.world().resource::<TypedText>();
+        assert!(!typed.apply(&mut value));
+        assert!(typed.submitted(), "Enter should submit without adding text");
+
+        app.update();
+        assert!(app.world().resource::<TypedText>().0.is_empty(), "Edits should only last one frame");
+    }
+
+    #[test]
+    fn test_shortcuts_and_arrows_arrive_as_text_edits() {
+        // Ctrl shortcuts and arrow keys arrive as edits and never insert text
+        let mut app = typing_app(EditorKeymap::default());
+        let press = |key_code, logical_key, text| key(key_code, logical_key, text, ButtonState::Pressed);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ShiftLeft);
+        app.world_mut().write_message(press(KeyCode::Home, Key::Home, None));
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ControlLeft);
+        app.world_mut().write_message(press(KeyCode::KeyA, Key::Character("a".into()), Some("\u{1}")));
+        app.world_mut().write_message(press(KeyCode::KeyV, Key::Character("м".into()), Some("\u{16}")));
+        app.update();
+        assert_eq!(
+            app.world().resource::<TypedText>().0,
+            vec![
+                TextEdit::Move { motion: CaretMotion::Home, select: true },
+                TextEdit::SelectAll,
+                TextEdit::Paste,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_property_fields_commit_cancel_and_tab() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.register_type::<Stats>();
+        app.init_resource::<TypedText>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.init_resource::<EditorState>();
+        app.init_resource::<InspectorUiState>();
+        app.insert_resource(EditorClipboard(Box::new(MemoryClipboard::default())));
+        app.add_systems(Update, text_input_system);
+
+        let target = app.world_mut().spawn(Stats { speed: 1.0, label: "a".to_string() }).id();
+        let property = |field: &str, value: &str| {
+            (
+                PropertyInput { entity: target, component_type_id: std::any::TypeId::of::<Stats>(), field_name: field.to_string() },
+                TextField::new(value),
+            )
+        };
+        let speed = app.world_mut().spawn(property("speed", "1.0")).id();
+        let label = app.world_mut().spawn(property("label", "a")).id();
+        app.world_mut().spawn(InspectorPanel).add_children(&[speed, label]);
+
+        let type_edits = |app: &mut App, edits: Vec<TextEdit>| {
+            app.world_mut().resource_mut::<TypedText>().0 = edits;
+            app.update();
+            app.world_mut().resource_mut::<TypedText>().0.clear();
+        };
+        let focused = |app: &App| app.world().resource::<EditorState>().focused_input;
+
+        // Tab commits and moves to the next field in inspector order
+        app.world_mut().resource_mut::<EditorState>().focused_input = Some(speed);
+        type_edits(&mut app, vec![TextEdit::SelectAll, TextEdit::Insert("2.5".to_string()), TextEdit::FocusNext { backwards: false }]);
+        assert_eq!(app.world().get::<Stats>(target).unwrap().speed, 2.5);
+        assert_eq!(focused(&app), Some(label));
+
+        // Escape drops the edit and releases focus
+        type_edits(&mut app, vec![TextEdit::Insert("zzz".to_string()), TextEdit::Cancel]);
+        assert_eq!(app.world().get::<TextField>(label).unwrap().text, "a");
+        assert_eq!(app.world().get::<Stats>(target).unwrap().label, "a");
+        assert_eq!(focused(&app), None);
+
+        // Shift+Tab wraps around
+        app.world_mut().resource_mut::<EditorState>().focused_input = Some(speed);
+        type_edits(&mut app, vec![TextEdit::FocusNext { backwards: true }]);
+        assert_eq!(focused(&app), Some(label));
+
+        // Clicking elsewhere commits
+        type_edits(&mut app, vec![TextEdit::Insert("b".to_string())]);
+        assert_eq!(app.world().get::<Stats>(target).unwrap().label, "a", "Typing alone shouldn't commit");
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+        app.update();
+        assert_eq!(app.world().get::<Stats>(target).unwrap().label, "ab");
+        assert_eq!(focused(&app), None);
+    }
+
+    #[test]
+    fn test_focused_text_field_consumes_keys() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<EditorKeys>();
+        app.init_resource::<EditorState>();
+        app.add_systems(Update, route_keyboard_input);
+        let field = app.world_mut().spawn(TextField::new("")).id();
+        let press = |app: &mut App, key| {
+            let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keys.clear();
+            keys.press(key);
+        };
+
+        // Held camera keys reach the camera
+        press(&mut app, KeyCode::KeyW);
+        app.update();
+        assert!(app.world().resource::<EditorKeys>().0.just_pressed(KeyCode::KeyW));
+
+        // Typing takes letters away, so the held key is released, but function keys still pass
+        app.world_mut().resource_mut::<EditorState>().focused_input = Some(field);
+        press(&mut app, KeyCode::F1);
+        app.update();
+        let routed = &app.world().resource::<EditorKeys>().0;
+        assert!(!routed.pressed(KeyCode::KeyW) && routed.just_released(KeyCode::KeyW));
+        assert!(routed.just_pressed(KeyCode::F1));
+
+        press(&mut app, KeyCode::KeyD);
+        app.update();
+        assert!(!app.world().resource::<EditorKeys>().0.pressed(KeyCode::KeyD));
+
+        // Once focus is gone, keys are forwarded again
+        app.world_mut().resource_mut::<EditorState>().focused_input = None;
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        app.update();
+        let routed = &app.world().resource::<EditorKeys>().0;
+        assert!(routed.pressed(KeyCode::KeyW) && routed.pressed(KeyCode::KeyD));
+    }
+}